@@ -0,0 +1,117 @@
+/// A list left open before the cursor, with the elements written so far. Atoms are
+/// kept as written, nested lists and strings only as markers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpenList {
+    pub elements: Vec<String>,
+}
+
+const NESTED_LIST: &str = "()";
+const STRING: &str = "\"\"";
+
+impl OpenList {
+
+    /// The function called by the list, when its first element is written.
+    pub fn function(&self) -> Option<&str> {
+        match self.elements.first() {
+            Some(element) if element != NESTED_LIST && element != STRING => Some(element),
+            _ => None
+        }
+    }
+
+    /// Index of the argument being written, when the function is known.
+    pub fn active_argument(&self) -> Option<usize> {
+        self.function().map(|_| self.elements.len() - 1)
+    }
+}
+
+/// The lists left open in the text, outermost first. The text is scanned rather than
+/// parsed, as it is usually incomplete while editing. The token under the cursor is
+/// still being written, so it is not counted as an element.
+pub fn open_lists(text: &str) -> Vec<OpenList> {
+    let mut lists: Vec<OpenList> = vec![];
+    let mut token = String::new();
+    let mut in_string = false;
+    let mut in_comment = false;
+    let mut escaped = false;
+
+    fn end_token(token: &mut String, lists: &mut [OpenList]) {
+        if token.is_empty() {
+            return;
+        }
+        if let Some(list) = lists.last_mut() {
+            list.elements.push(token.clone());
+        }
+        token.clear();
+    }
+
+    for character in text.chars() {
+        if in_comment {
+            in_comment = character != '\n';
+            continue;
+        }
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if character == '\\' {
+                escaped = true;
+            } else if character == '"' {
+                in_string = false;
+                token.push_str(STRING);
+            }
+            continue;
+        }
+        match character {
+            '(' | '{' => {
+                end_token(&mut token, &mut lists);
+                lists.push(OpenList { elements: vec![] });
+            },
+            ')' | '}' => {
+                end_token(&mut token, &mut lists);
+                lists.pop();
+                // the closed list is an element of the enclosing one.
+                if let Some(list) = lists.last_mut() {
+                    list.elements.push(NESTED_LIST.to_string());
+                }
+            },
+            ';' => {
+                end_token(&mut token, &mut lists);
+                in_comment = true;
+            },
+            '"' => {
+                // unicode strings are prefixed with `u`.
+                token.clear();
+                in_string = true;
+            },
+            character if character.is_whitespace() || character == ',' => {
+                end_token(&mut token, &mut lists);
+            },
+            character => token.push(character),
+        }
+    }
+    lists
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    fn innermost_call(text: &str) -> Option<(String, usize)> {
+        let list = open_lists(text).pop()?;
+        Some((list.function()?.to_string(), list.active_argument()?))
+    }
+
+    #[test]
+    fn test_open_lists() {
+        assert_eq!(innermost_call("(define-public (f) (var-get "), Some(("var-get".to_string(), 0)));
+        assert_eq!(innermost_call("(var-set counter (+ u1 u2) "), Some(("var-set".to_string(), 2)));
+        assert_eq!(innermost_call("(map-get? bal"), Some(("map-get?".to_string(), 0)));
+        assert_eq!(innermost_call("(print u\"(var-get \" ;; (map-get? \n"), Some(("print".to_string(), 1)));
+        assert_eq!(innermost_call("(var-g"), None);
+        assert_eq!(innermost_call("(var-get counter)"), None);
+
+        let lists = open_lists("(contract-call? .counter add (");
+        assert_eq!(lists.len(), 2);
+        assert_eq!(lists[0].elements, vec!["contract-call?", ".counter", "add"]);
+        assert_eq!(lists[1].function(), None);
+    }
+}
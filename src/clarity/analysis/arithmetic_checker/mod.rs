@@ -0,0 +1,448 @@
+use std::convert::TryFrom;
+
+use crate::clarity::analysis::types::{ContractAnalysis, LintPass};
+use crate::clarity::analysis::type_checker::contexts::TypeMap;
+use crate::clarity::analysis::type_checker::natives::{TypedNativeFunction, SimpleNativeFunction};
+use crate::clarity::analysis::warnings::{CheckWarning, CheckWarnings};
+use crate::clarity::representations::{SymbolicExpression, ClarityName};
+use crate::clarity::representations::SymbolicExpressionType::{Atom, AtomValue, List, LiteralValue};
+use crate::clarity::types::{FunctionType, TypeSignature, Value};
+use crate::clarity::functions::{DefineFunctions, NativeFunctions};
+
+/// Reports the arithmetic aborting the transaction at runtime: uint subtractions which
+/// can go below zero, divisors which can be zero, and operations on constants
+/// overflowing 128 bits.
+///
+/// The values are tracked as intervals, from the literals and the types. An operation
+/// is guarded when a condition checked before by an `asserts!`, or by an `if` for its
+/// then branch, reads its operands.
+pub struct ArithmeticChecker <'a> {
+    type_map: Option<&'a TypeMap>,
+    constants: Vec<(ClarityName, Interval)>,
+    bindings: Vec<(ClarityName, Interval)>,
+    guards: Vec<SymbolicExpression>,
+    warnings: Vec<CheckWarning>,
+}
+
+/// Bounds, inclusive, of an int or a uint.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Interval {
+    Int(i128, i128),
+    UInt(u128, u128),
+}
+
+impl Interval {
+    fn exact(value: &Value) -> Option<Interval> {
+        match value {
+            Value::Int(value) => Some(Interval::Int(*value, *value)),
+            Value::UInt(value) => Some(Interval::UInt(*value, *value)),
+            _ => None
+        }
+    }
+
+    fn of_type(type_signature: &TypeSignature) -> Option<Interval> {
+        match type_signature {
+            TypeSignature::IntType => Some(Interval::Int(i128::MIN, i128::MAX)),
+            TypeSignature::UIntType => Some(Interval::UInt(0, u128::MAX)),
+            _ => None
+        }
+    }
+
+    fn full(&self) -> Interval {
+        match self {
+            Interval::Int(..) => Interval::Int(i128::MIN, i128::MAX),
+            Interval::UInt(..) => Interval::UInt(0, u128::MAX),
+        }
+    }
+
+    fn zero(&self) -> Interval {
+        match self {
+            Interval::Int(..) => Interval::Int(0, 0),
+            Interval::UInt(..) => Interval::UInt(0, 0),
+        }
+    }
+
+    fn is_exact(&self) -> bool {
+        match self {
+            Interval::Int(min, max) => min == max,
+            Interval::UInt(min, max) => min == max,
+        }
+    }
+
+    fn contains_zero(&self) -> bool {
+        match self {
+            Interval::Int(min, max) => *min <= 0 && 0 <= *max,
+            Interval::UInt(min, _) => *min == 0,
+        }
+    }
+}
+
+/// Outcome of an operation on two intervals.
+struct Operation {
+    result: Interval,
+    /// Whether the operation aborts for some of the operands.
+    can_abort: bool,
+}
+
+impl <'a> LintPass for ArithmeticChecker <'a> {
+
+    fn run_lint(contract_analysis: &ContractAnalysis) -> Vec<CheckWarning> {
+        let mut command = ArithmeticChecker::new(contract_analysis.type_map.as_ref());
+        command.run(&contract_analysis.expressions)
+    }
+}
+
+impl <'a> ArithmeticChecker <'a> {
+
+    fn new(type_map: Option<&'a TypeMap>) -> Self {
+        Self {
+            type_map,
+            constants: vec![],
+            bindings: vec![],
+            guards: vec![],
+            warnings: vec![],
+        }
+    }
+
+    pub fn run(&mut self, expressions: &[SymbolicExpression]) -> Vec<CheckWarning> {
+        for exp in expressions.iter() {
+            match DefineFunctions::try_parse(exp) {
+                Some((DefineFunctions::Constant, args)) if args.len() == 2 => {
+                    let value = self.check_expression(&args[1]);
+                    if let (Some(name), Some(value)) = (args[0].match_atom(), value) {
+                        self.constants.push((name.clone(), value));
+                    }
+                },
+                Some((DefineFunctions::PrivateFunction, args)) | Some((DefineFunctions::PublicFunction, args))
+                | Some((DefineFunctions::ReadOnlyFunction, args)) => {
+                    let signature = args.first().and_then(|signature| signature.match_list()).unwrap_or_default();
+                    for parameter in signature.iter().skip(1).filter_map(|parameter| parameter.match_list()) {
+                        let name = parameter.first().and_then(|name| name.match_atom());
+                        let interval = parameter.get(1).and_then(|type_name| type_name.match_atom())
+                            .and_then(|type_name| match type_name.as_str() {
+                                "int" => Interval::of_type(&TypeSignature::IntType),
+                                "uint" => Interval::of_type(&TypeSignature::UIntType),
+                                _ => None
+                            });
+                        if let (Some(name), Some(interval)) = (name, interval) {
+                            self.bindings.push((name.clone(), interval));
+                        }
+                    }
+                    args.iter().skip(1).for_each(|body| { self.check_expression(body); });
+                    self.bindings.clear();
+                    self.guards.clear();
+                },
+                Some((DefineFunctions::PersistedVariable, args)) => {
+                    args.iter().skip(2).for_each(|initial| { self.check_expression(initial); });
+                },
+                Some(_) => {},
+                None => {
+                    self.check_expression(exp);
+                }
+            }
+        }
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// Checks the expression, returning the interval of its value when it is a number.
+    fn check_expression(&mut self, exp: &SymbolicExpression) -> Option<Interval> {
+        let list = match exp.expr {
+            AtomValue(ref value) | LiteralValue(ref value) => return Interval::exact(value),
+            Atom(ref name) => {
+                let interval = self.bindings.iter().rev()
+                    .chain(self.constants.iter().rev())
+                    .find(|(binding, _)| binding == name)
+                    .map(|(_, interval)| *interval);
+                return interval.or_else(|| self.typed_interval(exp));
+            },
+            List(ref list) => list,
+            _ => return None
+        };
+        let (function, args) = list.split_first()?;
+        let native_function = match function.match_atom().and_then(|name| NativeFunctions::lookup_by_name(name)) {
+            Some(native_function) => native_function,
+            None => {
+                args.iter().for_each(|arg| { self.check_expression(arg); });
+                return self.typed_interval(exp);
+            }
+        };
+        match native_function {
+            NativeFunctions::Let => {
+                let scope = self.bindings.len();
+                for binding in args.first().and_then(|bindings| bindings.match_list()).unwrap_or_default() {
+                    let pair = match binding.match_list() {
+                        Some(pair) if pair.len() == 2 => pair,
+                        _ => continue
+                    };
+                    let value = self.check_expression(&pair[1]);
+                    if let (Some(name), Some(value)) = (pair[0].match_atom(), value) {
+                        self.bindings.push((name.clone(), value));
+                    }
+                }
+                let value = args.iter().skip(1).map(|body| self.check_expression(body)).last().flatten();
+                self.bindings.truncate(scope);
+                value
+            },
+            NativeFunctions::Asserts => {
+                args.iter().for_each(|arg| { self.check_expression(arg); });
+                // the function aborts when the check fails.
+                if let Some(condition) = args.first() {
+                    self.guards.push(condition.clone());
+                }
+                None
+            },
+            NativeFunctions::If => {
+                let condition = args.first()?;
+                self.check_expression(condition);
+                // the else branch runs when the check fails.
+                let scope = self.guards.len();
+                self.guards.push(condition.clone());
+                if let Some(then_branch) = args.get(1) {
+                    self.check_expression(then_branch);
+                }
+                self.guards.truncate(scope);
+                if let Some(else_branch) = args.get(2) {
+                    self.check_expression(else_branch);
+                }
+                self.typed_interval(exp)
+            },
+            _ if is_arithmetic(&native_function) => self.check_arithmetic(exp, native_function, args),
+            _ => {
+                args.iter().for_each(|arg| { self.check_expression(arg); });
+                self.typed_interval(exp)
+            }
+        }
+    }
+
+    fn check_arithmetic(&mut self, exp: &SymbolicExpression, function: NativeFunctions, args: &[SymbolicExpression]) -> Option<Interval> {
+        let operands: Vec<_> = args.iter().map(|arg| self.check_expression(arg)).collect();
+        // operands share their type: the known ones tell the type of the others.
+        let kind = operands.iter().flatten().next().copied().or_else(|| self.typed_interval(exp))?;
+        let mut operands: Vec<_> = operands.into_iter().map(|operand| operand.unwrap_or_else(|| kind.full())).collect();
+        let is_constant = operands.iter().all(|operand| operand.is_exact());
+        // (- x) is (- 0 x).
+        if matches!(function, NativeFunctions::Subtract) && operands.len() == 1 {
+            operands.insert(0, kind.zero());
+        }
+
+        let mut result = *operands.first()?;
+        for (index, operand) in operands.iter().enumerate().skip(1) {
+            let operation = apply(&function, &result, operand);
+            // the operands read so far, and the one applied.
+            let index = index + args.len() - operands.len();
+            let (read, arg) = (&args[..index], &args[index..=index]);
+            let warning = match function {
+                NativeFunctions::Subtract if operation.can_abort && matches!(kind, Interval::UInt(..)) => {
+                    if !is_constant && self.is_guarded(read) && self.is_guarded(arg) {
+                        None
+                    } else {
+                        Some(CheckWarnings::UIntUnderflow)
+                    }
+                },
+                NativeFunctions::Divide | NativeFunctions::Modulo if operand.contains_zero() => {
+                    let is_guarded = !operand.is_exact() && self.is_guarded(arg);
+                    if is_guarded { None } else { Some(CheckWarnings::DivisionByZero) }
+                },
+                _ if operation.can_abort && is_constant => Some(CheckWarnings::ArithmeticOverflow(function.get_name())),
+                _ => None
+            };
+            if let Some(warning) = warning {
+                self.warnings.push(CheckWarning::new(warning, vec![exp.clone()]));
+                return Some(kind.full());
+            }
+            result = operation.result;
+        }
+        Some(result)
+    }
+
+    /// Whether a guard reads every operand which is not a literal.
+    fn is_guarded(&self, operands: &[SymbolicExpression]) -> bool {
+        operands.iter()
+            .filter(|operand| operand.match_atom_value().is_none() && operand.match_literal_value().is_none())
+            .all(|operand| self.guards.iter().any(|guard| reads(guard, operand)))
+    }
+
+    fn typed_interval(&self, exp: &SymbolicExpression) -> Option<Interval> {
+        self.type_map.and_then(|type_map| type_map.get_type(exp)).and_then(Interval::of_type)
+    }
+}
+
+fn is_arithmetic(function: &NativeFunctions) -> bool {
+    match TypedNativeFunction::type_native_function(function) {
+        TypedNativeFunction::Simple(SimpleNativeFunction(FunctionType::ArithmeticVariadic)) => true,
+        // xor never aborts.
+        TypedNativeFunction::Simple(SimpleNativeFunction(FunctionType::ArithmeticBinary)) =>
+            !matches!(function, NativeFunctions::BitwiseXOR),
+        _ => false
+    }
+}
+
+fn apply(function: &NativeFunctions, a: &Interval, b: &Interval) -> Operation {
+    match (a, b) {
+        (Interval::UInt(a_min, a_max), Interval::UInt(b_min, b_max)) => apply_uint(function, (*a_min, *a_max), (*b_min, *b_max)),
+        (Interval::Int(a_min, a_max), Interval::Int(b_min, b_max)) => apply_int(function, (*a_min, *a_max), (*b_min, *b_max)),
+        _ => Operation { result: a.full(), can_abort: false }
+    }
+}
+
+fn apply_uint(function: &NativeFunctions, (a_min, a_max): (u128, u128), (b_min, b_max): (u128, u128)) -> Operation {
+    let full = Interval::UInt(0, u128::MAX);
+    match function {
+        NativeFunctions::Add => Operation {
+            result: Interval::UInt(a_min.saturating_add(b_min), a_max.saturating_add(b_max)),
+            can_abort: a_max.checked_add(b_max).is_none(),
+        },
+        NativeFunctions::Subtract => Operation {
+            result: Interval::UInt(a_min.saturating_sub(b_max), a_max.saturating_sub(b_min)),
+            can_abort: a_min < b_max,
+        },
+        NativeFunctions::Multiply => Operation {
+            result: Interval::UInt(a_min.saturating_mul(b_min), a_max.saturating_mul(b_max)),
+            can_abort: a_max.checked_mul(b_max).is_none(),
+        },
+        NativeFunctions::Divide => Operation {
+            result: Interval::UInt(a_min / b_max.max(1), a_max / b_min.max(1)),
+            can_abort: b_min == 0,
+        },
+        NativeFunctions::Modulo => Operation {
+            result: Interval::UInt(0, a_max.min(b_max.saturating_sub(1))),
+            can_abort: b_min == 0,
+        },
+        NativeFunctions::Power => {
+            let power = u32::try_from(b_max).ok().and_then(|exponent| a_max.checked_pow(exponent));
+            match (power, u32::try_from(b_min)) {
+                (Some(max), Ok(exponent)) => Operation { result: Interval::UInt(a_min.saturating_pow(exponent), max), can_abort: false },
+                _ => Operation { result: full, can_abort: true }
+            }
+        },
+        _ => Operation { result: full, can_abort: false }
+    }
+}
+
+fn apply_int(function: &NativeFunctions, a: (i128, i128), b: (i128, i128)) -> Operation {
+    let full = Interval::Int(i128::MIN, i128::MAX);
+    let bounds = |operation: fn(i128, i128) -> Option<i128>| {
+        let corners = [operation(a.0, b.0), operation(a.0, b.1), operation(a.1, b.0), operation(a.1, b.1)];
+        if corners.iter().any(|corner| corner.is_none()) {
+            return Operation { result: full, can_abort: true };
+        }
+        let corners: Vec<_> = corners.iter().flatten().collect();
+        let min = corners.iter().copied().min().copied().unwrap_or(i128::MIN);
+        let max = corners.iter().copied().max().copied().unwrap_or(i128::MAX);
+        Operation { result: Interval::Int(min, max), can_abort: false }
+    };
+    match function {
+        NativeFunctions::Add => bounds(i128::checked_add),
+        NativeFunctions::Subtract => bounds(i128::checked_sub),
+        NativeFunctions::Multiply => bounds(i128::checked_mul),
+        NativeFunctions::Divide | NativeFunctions::Modulo => Operation {
+            result: full,
+            can_abort: b.0 <= 0 && 0 <= b.1,
+        },
+        NativeFunctions::Power if a.0 == a.1 && b.0 == b.1 => {
+            let power = u32::try_from(b.0).ok().and_then(|exponent| a.0.checked_pow(exponent));
+            match power {
+                Some(power) => Operation { result: Interval::Int(power, power), can_abort: false },
+                None => Operation { result: full, can_abort: true }
+            }
+        },
+        _ => Operation { result: full, can_abort: false }
+    }
+}
+
+/// Whether the expression is read by the condition.
+fn reads(condition: &SymbolicExpression, exp: &SymbolicExpression) -> bool {
+    if same_expression(condition, exp) {
+        return true;
+    }
+    match condition.match_list() {
+        Some(list) => list.iter().any(|item| reads(item, exp)),
+        None => false
+    }
+}
+
+/// Whether the expressions are written the same, wherever they are.
+fn same_expression(a: &SymbolicExpression, b: &SymbolicExpression) -> bool {
+    match (&a.expr, &b.expr) {
+        (List(a), List(b)) => a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| same_expression(a, b)),
+        (a, b) => a == b
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::clarity::analysis::{self, AnalysisDatabase};
+    use crate::clarity::ast;
+    use crate::clarity::costs::LimitedCostTracker;
+    use crate::clarity::types::QualifiedContractIdentifier;
+
+    fn warnings(contract: &str) -> Vec<(CheckWarnings, u32)> {
+        let contract_identifier = QualifiedContractIdentifier::transient();
+        let mut contract_ast = ast::build_ast(&contract_identifier, contract, &mut ()).unwrap();
+        let mut db = AnalysisDatabase::new();
+        let (contract_analysis, _) = analysis::run_analysis_collecting_errors(
+            &contract_identifier, &mut contract_ast.expressions, &mut db, false, LimitedCostTracker::new_max_limit());
+        ArithmeticChecker::run_lint(&contract_analysis).into_iter()
+            .map(|warning| (warning.warning, warning.diagnostic.spans[0].start_line))
+            .collect()
+    }
+
+    #[test]
+    fn test_underflows() {
+        let contract = "(define-data-var supply uint u100)
+                        (define-constant fee u2)
+                        (define-read-only (f (a uint) (b uint))
+                            (begin
+                                (- a b)
+                                (- a)
+                                (- (+ a u10) u5)
+                                (- fee u3)
+                                (- (var-get supply) fee)
+                                (if (>= a b) (- a b) u0)
+                                (if (>= a b) u0 (- a b))
+                                (- 10 20)))
+                        (define-read-only (g (a uint))
+                            (begin
+                                (asserts! (> a u0) (err u1))
+                                (ok (- a u1))))";
+        assert_eq!(warnings(contract), vec![
+            (CheckWarnings::UIntUnderflow, 5),
+            (CheckWarnings::UIntUnderflow, 6),
+            (CheckWarnings::UIntUnderflow, 8),
+            (CheckWarnings::UIntUnderflow, 9),
+            (CheckWarnings::UIntUnderflow, 11),
+        ]);
+    }
+
+    #[test]
+    fn test_divisions_by_zero() {
+        let contract = "(define-read-only (f (a uint) (b uint))
+                            (begin
+                                (/ a b)
+                                (mod a u0)
+                                (/ a (+ b u1))
+                                (/ a u2 b)
+                                (if (> b u0) (/ a b) u0)
+                                (if (> b u0) u0 (/ a b))))";
+        assert_eq!(warnings(contract), vec![
+            (CheckWarnings::DivisionByZero, 3),
+            (CheckWarnings::DivisionByZero, 4),
+            (CheckWarnings::DivisionByZero, 6),
+            (CheckWarnings::DivisionByZero, 8),
+        ]);
+    }
+
+    #[test]
+    fn test_constant_overflows() {
+        let contract = "(define-constant big (pow u2 u128))
+                        (define-constant fine (pow u2 u127))
+                        (define-constant also-big (* fine u2))
+                        (define-constant negative (pow -2 127))
+                        (define-read-only (f (a uint)) (pow a u200))";
+        assert_eq!(warnings(contract), vec![
+            (CheckWarnings::ArithmeticOverflow("pow".to_string()), 1),
+            (CheckWarnings::ArithmeticOverflow("*".to_string()), 3),
+        ]);
+    }
+}
@@ -378,7 +378,111 @@ impl DiagnosableError for CheckErrors {
             CheckErrors::BadLetSyntax => Some(format!("'let' syntax example: (let ((supply 1000) (ttl 60)) <next-expression>)")),
             CheckErrors::TraitReferenceUnknown(_) => Some(format!("traits should be either defined, with define-trait, or imported, with use-trait.")),
             CheckErrors::NoSuchBlockInfoProperty(_) => Some(format!("properties available: time, header-hash, burnchain-header-hash, vrf-seed")),
+            CheckErrors::PublicFunctionMustReturnResponse(_) => Some(format!("the returned value can be wrapped in (ok ...) or (err ...)")),
+            CheckErrors::WriteAttemptedInReadOnly => Some(format!("functions writing data should be defined with define-public or define-private")),
             _ => None
         }
     }
+
+    fn code(&self) -> String {
+        // codes are published, a variant keeps its number once assigned.
+        let number = match self {
+            CheckErrors::CostOverflow => 1,
+            CheckErrors::CostBalanceExceeded(..) => 2,
+            CheckErrors::MemoryBalanceExceeded(..) => 3,
+            CheckErrors::ValueTooLarge => 4,
+            CheckErrors::TypeSignatureTooDeep => 5,
+            CheckErrors::ExpectedName => 6,
+            CheckErrors::BadMatchOptionSyntax(..) => 7,
+            CheckErrors::BadMatchResponseSyntax(..) => 8,
+            CheckErrors::BadMatchInput(..) => 9,
+            CheckErrors::UnknownListConstructionFailure => 10,
+            CheckErrors::ListTypesMustMatch => 11,
+            CheckErrors::ConstructedListTooLarge => 12,
+            CheckErrors::TypeError(..) => 13,
+            CheckErrors::TypeLiteralError(..) => 14,
+            CheckErrors::TypeValueError(..) => 15,
+            CheckErrors::NoSuperType(..) => 16,
+            CheckErrors::InvalidTypeDescription => 17,
+            CheckErrors::UnknownTypeName(..) => 18,
+            CheckErrors::UnionTypeError(..) => 19,
+            CheckErrors::UnionTypeValueError(..) => 20,
+            CheckErrors::ExpectedLiteral => 21,
+            CheckErrors::ExpectedOptionalType(..) => 22,
+            CheckErrors::ExpectedResponseType(..) => 23,
+            CheckErrors::ExpectedOptionalOrResponseType(..) => 24,
+            CheckErrors::ExpectedOptionalValue(..) => 25,
+            CheckErrors::ExpectedResponseValue(..) => 26,
+            CheckErrors::ExpectedOptionalOrResponseValue(..) => 27,
+            CheckErrors::CouldNotDetermineResponseOkType => 28,
+            CheckErrors::CouldNotDetermineResponseErrType => 29,
+            CheckErrors::CouldNotDetermineMatchTypes => 30,
+            CheckErrors::TypeAlreadyAnnotatedFailure => 31,
+            CheckErrors::TypeAnnotationExpectedFailure => 32,
+            CheckErrors::CheckerImplementationFailure => 33,
+            CheckErrors::BadTokenName => 34,
+            CheckErrors::DefineFTBadSignature => 35,
+            CheckErrors::DefineNFTBadSignature => 36,
+            CheckErrors::NoSuchNFT(..) => 37,
+            CheckErrors::NoSuchFT(..) => 38,
+            CheckErrors::BadTransferSTXArguments => 39,
+            CheckErrors::BadTransferFTArguments => 40,
+            CheckErrors::BadTransferNFTArguments => 41,
+            CheckErrors::BadMintFTArguments => 42,
+            CheckErrors::BadTupleFieldName => 43,
+            CheckErrors::ExpectedTuple(..) => 44,
+            CheckErrors::NoSuchTupleField(..) => 45,
+            CheckErrors::EmptyTuplesNotAllowed => 46,
+            CheckErrors::BadTupleConstruction => 47,
+            CheckErrors::TupleExpectsPairs => 48,
+            CheckErrors::NoSuchDataVariable(..) => 49,
+            CheckErrors::BadMapName => 50,
+            CheckErrors::NoSuchMap(..) => 51,
+            CheckErrors::DefineFunctionBadSignature => 52,
+            CheckErrors::BadFunctionName => 53,
+            CheckErrors::BadMapTypeDefinition => 54,
+            CheckErrors::PublicFunctionMustReturnResponse(..) => 55,
+            CheckErrors::DefineVariableBadSignature => 56,
+            CheckErrors::ReturnTypesMustMatch(..) => 57,
+            CheckErrors::CircularReference(..) => 58,
+            CheckErrors::NoSuchContract(..) => 59,
+            CheckErrors::NoSuchPublicFunction(..) => 60,
+            CheckErrors::PublicFunctionNotReadOnly(..) => 61,
+            CheckErrors::ContractAlreadyExists(..) => 62,
+            CheckErrors::ContractCallExpectName => 63,
+            CheckErrors::NoSuchBlockInfoProperty(..) => 64,
+            CheckErrors::GetBlockInfoExpectPropertyName => 65,
+            CheckErrors::NameAlreadyUsed(..) => 66,
+            CheckErrors::NonFunctionApplication => 67,
+            CheckErrors::ExpectedListApplication => 68,
+            CheckErrors::ExpectedListOrBuffer(..) => 69,
+            CheckErrors::MaxLengthOverflow => 70,
+            CheckErrors::BadLetSyntax => 71,
+            CheckErrors::BadSyntaxBinding => 72,
+            CheckErrors::BadSyntaxExpectedListOfPairs => 73,
+            CheckErrors::MaxContextDepthReached => 74,
+            CheckErrors::UndefinedFunction(..) => 75,
+            CheckErrors::UndefinedVariable(..) => 76,
+            CheckErrors::RequiresAtLeastArguments(..) => 77,
+            CheckErrors::IncorrectArgumentCount(..) => 78,
+            CheckErrors::IfArmsMustMatch(..) => 79,
+            CheckErrors::MatchArmsMustMatch(..) => 80,
+            CheckErrors::DefaultTypesMustMatch(..) => 81,
+            CheckErrors::TooManyExpressions => 82,
+            CheckErrors::IllegalOrUnknownFunctionApplication(..) => 83,
+            CheckErrors::UnknownFunction(..) => 84,
+            CheckErrors::TraitReferenceUnknown(..) => 85,
+            CheckErrors::TraitMethodUnknown(..) => 86,
+            CheckErrors::ExpectedTraitIdentifier => 87,
+            CheckErrors::ImportTraitBadSignature => 88,
+            CheckErrors::TraitReferenceNotAllowed => 89,
+            CheckErrors::BadTraitImplementation(..) => 90,
+            CheckErrors::DefineTraitBadSignature => 91,
+            CheckErrors::UnexpectedTraitOrFieldReference => 92,
+            CheckErrors::TraitBasedContractCallInReadOnly => 93,
+            CheckErrors::WriteAttemptedInReadOnly => 94,
+            CheckErrors::AtBlockClosureMustBeReadOnly => 95,
+        };
+        format!("CLR-T{:04}", number)
+    }
 }
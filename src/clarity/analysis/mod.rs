@@ -1,22 +1,36 @@
 pub mod types;
 pub mod errors;
+pub mod warnings;
 pub mod trait_checker;
 pub mod type_checker;
 pub mod read_only_checker;
+pub mod unused_checker;
+pub mod unchecked_response_checker;
+pub mod sender_checker;
+pub mod taint_checker;
+pub mod arithmetic_checker;
+pub mod unreachable_checker;
 pub mod analysis_db;
 pub mod contract_interface_builder;
 
-pub use self::types::{ContractAnalysis, AnalysisPass};
+pub use self::types::{ContractAnalysis, AnalysisPass, LintPass};
 use crate::clarity::representations::{SymbolicExpression};
 use crate::clarity::types::{TypeSignature, QualifiedContractIdentifier};
 use crate::clarity::costs::LimitedCostTracker;
 
 pub use self::errors::{CheckResult, CheckError, CheckErrors};
+pub use self::warnings::{CheckWarning, CheckWarnings};
 pub use self::analysis_db::{AnalysisDatabase};
 
 use self::read_only_checker::ReadOnlyChecker;
 use self::trait_checker::TraitChecker;
 use self::type_checker::TypeChecker;
+use self::unused_checker::UnusedChecker;
+use self::unchecked_response_checker::UncheckedResponseChecker;
+use self::sender_checker::SenderChecker;
+use self::taint_checker::TaintChecker;
+use self::arithmetic_checker::ArithmeticChecker;
+use self::unreachable_checker::UnreachableChecker;
 use self::contract_interface_builder::build_contract_interface;
 
 // Legacy function
@@ -49,3 +63,110 @@ pub fn run_analysis(contract_identifier: &QualifiedContractIdentifier,
         Err(e) => Err((e, contract_analysis.take_contract_cost_tracker()))
     }
 }
+
+/// Error-accumulating flavor of `run_analysis`: each top-level expression is checked
+/// independently, and every error found is reported along with the partial analysis.
+pub fn run_analysis_collecting_errors(contract_identifier: &QualifiedContractIdentifier, 
+                                      expressions: &mut [SymbolicExpression],
+                                      analysis_db: &mut AnalysisDatabase, 
+                                      save_contract: bool,
+                                      cost_tracker: LimitedCostTracker) -> (ContractAnalysis, Vec<CheckError>) {
+    let mut contract_analysis = ContractAnalysis::new(contract_identifier.clone(), expressions.to_vec(), cost_tracker);
+    let mut errors: Vec<CheckError> = vec![];
+    let passes_errors = vec![
+        ReadOnlyChecker::run_pass_collecting_errors(&mut contract_analysis, analysis_db),
+        TypeChecker::run_pass_collecting_errors(&mut contract_analysis, analysis_db),
+        TraitChecker::run_pass_collecting_errors(&mut contract_analysis, analysis_db),
+    ];
+    for error in passes_errors.into_iter().flatten() {
+        // malformed definitions are reported by every pass.
+        let is_duplicate = errors.iter()
+            .any(|e| e.err == error.err && e.diagnostic.spans == error.diagnostic.spans);
+        if !is_duplicate {
+            errors.push(error);
+        }
+    }
+    if save_contract && errors.is_empty() {
+        if let Err(e) = analysis_db.insert_contract(contract_identifier, &contract_analysis) {
+            errors.push(e);
+        }
+    }
+    (contract_analysis, errors)
+}
+
+/// Runs the lint passes on an analysed contract, returning their warnings in the order
+/// of the contract.
+pub fn run_lints(contract_analysis: &ContractAnalysis) -> Vec<CheckWarning> {
+    let mut warnings = UnusedChecker::run_lint(contract_analysis);
+    warnings.extend(UncheckedResponseChecker::run_lint(contract_analysis));
+    warnings.extend(SenderChecker::run_lint(contract_analysis));
+    let tainted = TaintChecker::run_lint(contract_analysis);
+    // unchecked senders and recipients are reported by both passes, the taint analysis
+    // tells how.
+    warnings.retain(|warning| !matches!(warning.warning, CheckWarnings::CallerSuppliedRecipient(_) | CheckWarnings::CallerSuppliedSender(_))
+        || !tainted.iter().any(|taint| taint.diagnostic.spans == warning.diagnostic.spans));
+    warnings.extend(tainted);
+    warnings.extend(ArithmeticChecker::run_lint(contract_analysis));
+    warnings.extend(UnreachableChecker::run_lint(contract_analysis));
+    warnings.sort_by_key(|warning| warning.diagnostic.spans.first()
+        .map(|span| (span.start_line, span.start_column)));
+    warnings
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::clarity::ast;
+
+    fn collect_errors(contract: &str) -> Vec<CheckErrors> {
+        let contract_identifier = QualifiedContractIdentifier::transient();
+        let mut contract_ast = ast::build_ast(&contract_identifier, contract, &mut ()).unwrap();
+        let mut db = AnalysisDatabase::new();
+        let (_, errors) = run_analysis_collecting_errors(&contract_identifier, &mut contract_ast.expressions,
+                                                         &mut db, false, LimitedCostTracker::new_max_limit());
+        errors.into_iter().map(|e| e.err).collect()
+    }
+
+    #[test]
+    fn test_errors_are_collected_per_definition() {
+        let contract = "(define-public (foo) (ok (+ 1 u1)))
+                        (define-public (bar) u1)
+                        (define-read-only (baz) (var-set counter u2))
+                        (define-data-var counter uint u0)
+                        (define-public (qux) (foo))
+                        (define-private (quux) (+ 1 u1))";
+        let errors = collect_errors(contract);
+        assert_eq!(errors, vec![
+            CheckErrors::WriteAttemptedInReadOnly,
+            CheckErrors::TypeError(TypeSignature::IntType, TypeSignature::UIntType),
+            CheckErrors::PublicFunctionMustReturnResponse(TypeSignature::UIntType),
+            CheckErrors::TypeError(TypeSignature::IntType, TypeSignature::UIntType),
+        ]);
+    }
+
+    #[test]
+    fn test_errors_of_dependents_are_kept() {
+        let contract = "(define-private (f) (+ u1 true))
+                        (define-private (g) (begin (+ 1 u1) (f)))
+                        (define-private (h) (f))";
+        assert_eq!(collect_errors(contract), vec![
+            CheckErrors::TypeError(TypeSignature::UIntType, TypeSignature::BoolType),
+            CheckErrors::TypeError(TypeSignature::IntType, TypeSignature::UIntType),
+        ]);
+    }
+
+    #[test]
+    fn test_lints_report_a_recipient_once() {
+        let contract = "(define-public (withdraw (recipient principal))
+                            (as-contract (stx-transfer? u1 tx-sender recipient)))";
+        let contract_identifier = QualifiedContractIdentifier::transient();
+        let mut contract_ast = ast::build_ast(&contract_identifier, contract, &mut ()).unwrap();
+        let mut db = AnalysisDatabase::new();
+        let (contract_analysis, _) = run_analysis_collecting_errors(&contract_identifier, &mut contract_ast.expressions,
+                                                                    &mut db, false, LimitedCostTracker::new_max_limit());
+        let warnings: Vec<_> = run_lints(&contract_analysis).into_iter().map(|warning| warning.warning).collect();
+        assert_eq!(warnings, vec![
+            CheckWarnings::UntrustedArgument("recipient".to_string(), "the recipient of stx-transfer?".to_string()),
+        ]);
+    }
+}
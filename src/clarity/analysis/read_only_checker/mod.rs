@@ -4,7 +4,7 @@ use crate::clarity::types::{Value, PrincipalData, get_definition_type_of_tuple_a
 use crate::clarity::functions::NativeFunctions;
 use crate::clarity::functions::DefineFunctionsParsed;
 use crate::clarity::types::TupleDefinitionType::{Implicit, Explicit};
-use crate::clarity::analysis::types::{ContractAnalysis, AnalysisPass};
+use crate::clarity::analysis::types::{ContractAnalysis, AnalysisPass, FailedDefinitions};
 
 use crate::clarity::functions::NativeVariables;
 use std::collections::HashMap;
@@ -25,6 +25,11 @@ impl <'a, 'b> AnalysisPass for ReadOnlyChecker <'a, 'b> {
         command.run(contract_analysis)?;
         Ok(())
     }
+
+    fn run_pass_collecting_errors(contract_analysis: &mut ContractAnalysis, analysis_db: &mut AnalysisDatabase) -> Vec<CheckError> {
+        let mut command = ReadOnlyChecker::new(analysis_db);
+        command.run_collecting_errors(contract_analysis)
+    }
 }
 
 impl <'a, 'b> ReadOnlyChecker <'a, 'b> {
@@ -39,16 +44,36 @@ impl <'a, 'b> ReadOnlyChecker <'a, 'b> {
     pub fn run(& mut self, contract_analysis: &mut ContractAnalysis) -> CheckResult<()> {
 
         for exp in contract_analysis.expressions.iter() {
-            let mut result = self.check_reads_only_valid(&exp);
-            if let Err(ref mut error) = result {
-                if !error.has_expression() {
-                    error.set_expression(&exp);
+            self.check_top_level_expression(exp)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn run_collecting_errors(&mut self, contract_analysis: &mut ContractAnalysis) -> Vec<CheckError> {
+        let mut errors = vec![];
+        let mut failed_definitions = FailedDefinitions::new();
+
+        for exp in contract_analysis.expressions.iter() {
+            if let Err(error) = self.check_top_level_expression(exp) {
+                if !failed_definitions.caused(&error.err) {
+                    errors.push(error);
                 }
+                failed_definitions.record(exp);
             }
-            result?
         }
 
-        Ok(())
+        errors
+    }
+
+    fn check_top_level_expression(&mut self, exp: &SymbolicExpression) -> CheckResult<()> {
+        let mut result = self.check_reads_only_valid(exp);
+        if let Err(ref mut error) = result {
+            if !error.has_expression() {
+                error.set_expression(exp);
+            }
+        }
+        result
     }
 
     fn check_define_function(&mut self, signature: &[SymbolicExpression], body: &SymbolicExpression) -> CheckResult<(ClarityName, bool)> {
@@ -83,10 +108,11 @@ impl <'a, 'b> ReadOnlyChecker <'a, 'b> {
                 },
                 ReadOnlyFunction { signature, body } => {
                     let (f_name, is_read_only) = self.check_define_function(signature, body)?;
+                    // the function is registered either way, so that checking can resume
+                    //   on the following expressions when errors are being collected.
+                    self.defined_functions.insert(f_name, is_read_only);
                     if !is_read_only {
                         return Err(CheckErrors::WriteAttemptedInReadOnly.into())
-                    } else {
-                        self.defined_functions.insert(f_name, is_read_only);
                     }
                 },
                 Map { .. } | NonFungibleToken { .. } | UnboundedFungibleToken { .. } => {
@@ -0,0 +1,290 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::clarity::analysis::types::{ContractAnalysis, LintPass};
+use crate::clarity::analysis::warnings::{CheckWarning, CheckWarnings};
+use crate::clarity::representations::{SymbolicExpression, ClarityName, Span};
+use crate::clarity::representations::SymbolicExpressionType::{Atom, List};
+use crate::clarity::functions::{DefineFunctions, NativeFunctions, NativeVariables};
+
+/// Reports the authorization checks relying on `tx-sender` in the functions other
+/// contracts can call, and the `as-contract` transfers from or to a principal supplied
+/// by the caller.
+///
+/// `tx-sender` is the principal which signed the transaction: any contract the user
+/// interacts with can call this one on their behalf. `contract-caller` is the principal
+/// calling this contract directly.
+pub struct SenderChecker {
+    /// Functions reachable through `contract-call?`: the public and read-only ones, and
+    /// the private ones they call.
+    reachable_functions: HashSet<ClarityName>,
+    /// Where the values of the parameters and let bindings in scope come from.
+    bindings: Vec<(ClarityName, Vec<Origin>)>,
+    warnings: Vec<CheckWarning>,
+}
+
+#[derive(Clone)]
+enum Origin {
+    TxSender(Span),
+    ContractCaller,
+    Parameter(ClarityName),
+}
+
+#[derive(Clone, Copy)]
+struct Context {
+    is_reachable: bool,
+    /// In `as-contract`, the senders are the contract itself.
+    is_contract: bool,
+}
+
+impl LintPass for SenderChecker {
+
+    fn run_lint(contract_analysis: &ContractAnalysis) -> Vec<CheckWarning> {
+        let mut command = SenderChecker::new();
+        command.run(&contract_analysis.expressions)
+    }
+}
+
+impl SenderChecker {
+
+    fn new() -> Self {
+        Self {
+            reachable_functions: HashSet::new(),
+            bindings: vec![],
+            warnings: vec![],
+        }
+    }
+
+    pub fn run(&mut self, expressions: &[SymbolicExpression]) -> Vec<CheckWarning> {
+        let functions: Vec<_> = expressions.iter().filter_map(function_definition).collect();
+        self.find_reachable_functions(&functions);
+
+        for (name, _, signature, body) in functions.iter() {
+            let context = Context {
+                is_reachable: self.reachable_functions.contains(*name),
+                is_contract: false,
+            };
+            for parameter in signature.iter().skip(1) {
+                if let Some(parameter) = parameter.match_list().and_then(|parameter| parameter.first()).and_then(|name| name.match_atom()) {
+                    self.bindings.push((parameter.clone(), vec![Origin::Parameter(parameter.clone())]));
+                }
+            }
+            self.check_expression(body, &context);
+            self.bindings.clear();
+        }
+
+        std::mem::take(&mut self.warnings)
+    }
+
+    fn find_reachable_functions(&mut self, functions: &[(&ClarityName, DefineFunctions, &[SymbolicExpression], &SymbolicExpression)]) {
+        let names: HashSet<_> = functions.iter().map(|(name, ..)| *name).collect();
+        let calls: HashMap<_, _> = functions.iter()
+            .map(|(name, _, _, body)| {
+                let mut called = HashSet::new();
+                collect_atoms(body, &names, &mut called);
+                (*name, called)
+            })
+            .collect();
+
+        let mut pending: Vec<_> = functions.iter()
+            .filter(|(_, define_type, ..)| matches!(define_type, DefineFunctions::PublicFunction | DefineFunctions::ReadOnlyFunction))
+            .map(|(name, ..)| *name)
+            .collect();
+        while let Some(name) = pending.pop() {
+            if self.reachable_functions.insert(name.clone()) {
+                pending.extend(calls.get(name).into_iter().flatten());
+            }
+        }
+    }
+
+    fn check_expression(&mut self, exp: &SymbolicExpression, context: &Context) {
+        let list = match exp.expr {
+            List(ref list) => list,
+            _ => return
+        };
+        let (function, args) = match list.split_first() {
+            Some(split) => split,
+            None => return
+        };
+        let native_function = function.match_atom().and_then(|name| NativeFunctions::lookup_by_name(name));
+        match native_function {
+            Some(NativeFunctions::Let) => {
+                let scope = self.bindings.len();
+                for binding in args.first().and_then(|bindings| bindings.match_list()).unwrap_or_default() {
+                    let pair = match binding.match_list() {
+                        Some(pair) if pair.len() == 2 => pair,
+                        _ => continue
+                    };
+                    self.check_expression(&pair[1], context);
+                    if let Some(name) = pair[0].match_atom() {
+                        let origins = self.origins(&pair[1], context);
+                        self.bindings.push((name.clone(), origins));
+                    }
+                }
+                args.iter().skip(1).for_each(|body| self.check_expression(body, context));
+                self.bindings.truncate(scope);
+                return;
+            },
+            Some(NativeFunctions::AsContract) => {
+                let context = Context { is_contract: true, ..*context };
+                args.iter().for_each(|body| self.check_expression(body, &context));
+                return;
+            },
+            Some(NativeFunctions::Equals) if context.is_reachable && !context.is_contract => {
+                self.check_authorization(args, context);
+            },
+            // (stx-transfer? amount sender recipient)
+            Some(NativeFunctions::StxTransfer) if context.is_contract => self.check_transfer(args.get(1), args.get(2), context),
+            // (ft-transfer? token amount sender recipient)
+            Some(NativeFunctions::TransferToken) | Some(NativeFunctions::TransferAsset) if context.is_contract =>
+                self.check_transfer(args.get(2), args.get(3), context),
+            _ => {}
+        }
+        args.iter().for_each(|arg| self.check_expression(arg, context));
+    }
+
+    /// Reports the `tx-sender` compared in `(is-eq ...)`, unless it is compared with
+    /// `contract-caller` to make sure the contract is called directly.
+    fn check_authorization(&mut self, args: &[SymbolicExpression], context: &Context) {
+        let origins: Vec<_> = args.iter().map(|arg| (arg, self.origins(arg, context))).collect();
+        let checks_caller = origins.iter()
+            .any(|(_, origins)| origins.iter().any(|origin| matches!(origin, Origin::ContractCaller)));
+        if checks_caller {
+            return;
+        }
+        for (arg, origins) in origins.into_iter() {
+            let tx_sender = origins.into_iter().find_map(|origin| match origin {
+                Origin::TxSender(tx_sender) => Some(tx_sender),
+                _ => None
+            });
+            if let Some(tx_sender) = tx_sender {
+                let mut warning = CheckWarning::new(CheckWarnings::TxSenderAuthorization, vec![arg.clone()]);
+                if tx_sender != arg.span {
+                    warning.diagnostic.add_related_information(tx_sender, "tx-sender is read here".to_string());
+                }
+                self.warnings.push(warning);
+            }
+        }
+    }
+
+    fn check_transfer(&mut self, sender: Option<&SymbolicExpression>, recipient: Option<&SymbolicExpression>, context: &Context) {
+        if let Some((sender, name)) = sender.and_then(|sender| self.parameter(sender, context).map(|name| (sender, name))) {
+            self.warnings.push(CheckWarning::new(CheckWarnings::CallerSuppliedSender(name.to_string()), vec![sender.clone()]));
+        }
+        if let Some((recipient, name)) = recipient.and_then(|recipient| self.parameter(recipient, context).map(|name| (recipient, name))) {
+            self.warnings.push(CheckWarning::new(CheckWarnings::CallerSuppliedRecipient(name.to_string()), vec![recipient.clone()]));
+        }
+    }
+
+    /// The parameter the value of the expression comes from, if any.
+    fn parameter(&self, exp: &SymbolicExpression, context: &Context) -> Option<ClarityName> {
+        self.origins(exp, context).into_iter().find_map(|origin| match origin {
+            Origin::Parameter(name) => Some(name),
+            _ => None
+        })
+    }
+
+    fn origins(&self, exp: &SymbolicExpression, context: &Context) -> Vec<Origin> {
+        let name = match exp.expr {
+            Atom(ref name) => name,
+            _ => return vec![]
+        };
+        if let Some((_, origins)) = self.bindings.iter().rev().find(|(binding, _)| binding == name) {
+            return origins.clone();
+        }
+        match NativeVariables::lookup_by_name(name) {
+            _ if context.is_contract => vec![],
+            Some(NativeVariables::TxSender) => vec![Origin::TxSender(exp.span.clone())],
+            Some(NativeVariables::ContractCaller) => vec![Origin::ContractCaller],
+            _ => vec![]
+        }
+    }
+}
+
+fn function_definition(exp: &SymbolicExpression) -> Option<(&ClarityName, DefineFunctions, &[SymbolicExpression], &SymbolicExpression)> {
+    let (define_type, args) = DefineFunctions::try_parse(exp)?;
+    match define_type {
+        DefineFunctions::PublicFunction | DefineFunctions::PrivateFunction | DefineFunctions::ReadOnlyFunction => {
+            let signature = args.first()?.match_list()?;
+            let name = signature.first()?.match_atom()?;
+            Some((name, define_type, signature, args.get(1)?))
+        },
+        _ => None
+    }
+}
+
+/// Names among the given ones, used in the expression.
+fn collect_atoms<'a>(exp: &SymbolicExpression, names: &HashSet<&'a ClarityName>, found: &mut HashSet<&'a ClarityName>) {
+    match exp.expr {
+        Atom(ref name) => {
+            if let Some(name) = names.get(name) {
+                found.insert(name);
+            }
+        },
+        List(ref list) => list.iter().for_each(|item| collect_atoms(item, names, found)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::clarity::ast;
+    use crate::clarity::types::QualifiedContractIdentifier;
+
+    fn warnings(contract: &str) -> Vec<(CheckWarnings, u32)> {
+        let contract_identifier = QualifiedContractIdentifier::transient();
+        let contract_ast = ast::build_ast(&contract_identifier, contract, &mut ()).unwrap();
+        SenderChecker::new().run(&contract_ast.expressions).into_iter()
+            .map(|warning| (warning.warning, warning.diagnostic.spans[0].start_line))
+            .collect()
+    }
+
+    #[test]
+    fn test_tx_sender_authorization() {
+        let contract = "(define-constant owner tx-sender)
+                        (define-private (is-owner) (is-eq tx-sender owner))
+                        (define-private (is-admin) (is-eq contract-caller owner))
+                        (define-private (is-direct) (is-eq tx-sender contract-caller))
+                        (define-read-only (is-owner-read-only) (is-eq tx-sender owner))
+                        (define-public (withdraw)
+                            (let ((caller tx-sender))
+                                (asserts! (or (is-owner) (is-admin) (is-direct)) (err u1))
+                                (asserts! (is-eq caller owner) (err u1))
+                                (as-contract (ok (is-eq tx-sender owner)))))";
+        let found = warnings(contract);
+        assert_eq!(found, vec![
+            (CheckWarnings::TxSenderAuthorization, 2),
+            (CheckWarnings::TxSenderAuthorization, 5),
+            (CheckWarnings::TxSenderAuthorization, 9),
+        ]);
+    }
+
+    #[test]
+    fn test_transfers_to_caller_supplied_principals() {
+        let contract = "(define-fungible-token token)
+                        (define-public (withdraw (amount uint) (recipient principal))
+                            (let ((to recipient))
+                                (try! (as-contract (stx-transfer? amount tx-sender to)))
+                                (try! (as-contract (ft-transfer? token amount tx-sender recipient)))
+                                (stx-transfer? amount tx-sender recipient)))";
+        assert_eq!(warnings(contract), vec![
+            (CheckWarnings::CallerSuppliedRecipient("recipient".to_string()), 4),
+            (CheckWarnings::CallerSuppliedRecipient("recipient".to_string()), 5),
+        ]);
+    }
+
+    #[test]
+    fn test_transfers_from_caller_supplied_principals() {
+        let contract = "(define-fungible-token token)
+                        (define-non-fungible-token badge uint)
+                        (define-public (move (amount uint) (owner principal))
+                            (let ((from owner))
+                                (try! (as-contract (ft-transfer? token amount from tx-sender)))
+                                (try! (as-contract (nft-transfer? badge amount owner tx-sender)))
+                                (ft-transfer? token amount owner tx-sender)))";
+        assert_eq!(warnings(contract), vec![
+            (CheckWarnings::CallerSuppliedSender("owner".to_string()), 5),
+            (CheckWarnings::CallerSuppliedSender("owner".to_string()), 6),
+        ]);
+    }
+}
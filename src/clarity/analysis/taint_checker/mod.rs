@@ -0,0 +1,370 @@
+use std::collections::HashSet;
+
+use crate::clarity::analysis::types::{ContractAnalysis, LintPass};
+use crate::clarity::analysis::warnings::{CheckWarning, CheckWarnings};
+use crate::clarity::representations::{SymbolicExpression, ClarityName, Span};
+use crate::clarity::representations::SymbolicExpressionType::{Atom, AtomValue, List, LiteralValue};
+use crate::clarity::types::Value;
+use crate::clarity::functions::{DefineFunctions, NativeFunctions, NativeVariables};
+
+/// Follows the arguments of the public functions, which anyone can set to anything,
+/// and reports them when they reach a transfer or a write without being checked by an
+/// `asserts!` or an `if` first.
+///
+/// Comparing `tx-sender` or `contract-caller` with a constant or a data var authorizes
+/// the caller, and with it every argument.
+pub struct TaintChecker {
+    constants: HashSet<ClarityName>,
+    bindings: Vec<Binding>,
+    /// Names of the arguments and bindings checked so far.
+    guarded: HashSet<ClarityName>,
+    is_authorized: bool,
+    warnings: Vec<CheckWarning>,
+}
+
+struct Binding {
+    name: ClarityName,
+    taint: Option<Taint>,
+}
+
+/// The argument a value is computed from, and the bindings it went through.
+#[derive(Clone)]
+struct Taint {
+    argument: ClarityName,
+    path: Vec<(Span, String)>,
+}
+
+impl LintPass for TaintChecker {
+
+    fn run_lint(contract_analysis: &ContractAnalysis) -> Vec<CheckWarning> {
+        let mut command = TaintChecker::new();
+        command.run(&contract_analysis.expressions)
+    }
+}
+
+impl TaintChecker {
+
+    fn new() -> Self {
+        Self {
+            constants: HashSet::new(),
+            bindings: vec![],
+            guarded: HashSet::new(),
+            is_authorized: false,
+            warnings: vec![],
+        }
+    }
+
+    pub fn run(&mut self, expressions: &[SymbolicExpression]) -> Vec<CheckWarning> {
+        self.constants = expressions.iter()
+            .filter_map(|exp| match DefineFunctions::try_parse(exp) {
+                Some((DefineFunctions::Constant, args)) => args.first().and_then(|name| name.match_atom()).cloned(),
+                _ => None
+            })
+            .collect();
+
+        for exp in expressions.iter() {
+            let (define_type, args) = match DefineFunctions::try_parse(exp) {
+                Some(define) => define,
+                None => continue
+            };
+            if !matches!(define_type, DefineFunctions::PublicFunction) {
+                continue;
+            }
+            let signature = args.first().and_then(|signature| signature.match_list()).unwrap_or_default();
+            for parameter in signature.iter().skip(1) {
+                let name = match parameter.match_list().and_then(|parameter| parameter.first()) {
+                    Some(name) => name,
+                    None => continue
+                };
+                if let Some(atom) = name.match_atom() {
+                    let taint = Taint {
+                        argument: atom.clone(),
+                        path: vec![(name.span.clone(), format!("'{}' is an argument of a public function", atom.as_str()))],
+                    };
+                    self.bindings.push(Binding { name: atom.clone(), taint: Some(taint) });
+                }
+            }
+            args.iter().skip(1).for_each(|body| self.check_expression(body, false));
+
+            self.bindings.clear();
+            self.guarded.clear();
+            self.is_authorized = false;
+        }
+
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// Checks the expression in evaluation order. In `as-contract`, the contract is the
+    /// sender of the transfers.
+    fn check_expression(&mut self, exp: &SymbolicExpression, is_contract: bool) {
+        let list = match exp.expr {
+            List(ref list) => list,
+            _ => return
+        };
+        let (function, args) = match list.split_first() {
+            Some(split) => split,
+            None => return
+        };
+        let native_function = function.match_atom().and_then(|name| NativeFunctions::lookup_by_name(name));
+        match native_function {
+            Some(NativeFunctions::Let) => {
+                let scope = self.bindings.len();
+                for binding in args.first().and_then(|bindings| bindings.match_list()).unwrap_or_default() {
+                    let pair = match binding.match_list() {
+                        Some(pair) if pair.len() == 2 => pair,
+                        _ => continue
+                    };
+                    self.check_expression(&pair[1], is_contract);
+                    self.bind(&pair[0], &pair[1]);
+                }
+                args.iter().skip(1).for_each(|body| self.check_expression(body, is_contract));
+                self.bindings.truncate(scope);
+            },
+            Some(NativeFunctions::Match) if args.len() == 4 || args.len() == 5 => {
+                self.check_expression(&args[0], is_contract);
+                let scope = self.bindings.len();
+                // (match input some-name some-branch none-branch)
+                // (match input ok-name ok-branch err-name err-branch)
+                self.bind(&args[1], &args[0]);
+                if args.len() == 5 {
+                    self.bind(&args[3], &args[0]);
+                }
+                args.iter().skip(2).for_each(|branch| self.check_expression(branch, is_contract));
+                self.bindings.truncate(scope);
+            },
+            Some(NativeFunctions::Asserts) => {
+                // the function aborts when the check fails.
+                args.iter().for_each(|arg| self.check_expression(arg, is_contract));
+                if let Some(condition) = args.first() {
+                    self.guard(condition);
+                }
+            },
+            Some(NativeFunctions::If) => {
+                if let Some(condition) = args.first() {
+                    self.check_expression(condition, is_contract);
+                    let (guarded, is_authorized) = (self.guarded.clone(), self.is_authorized);
+                    self.guard(condition);
+                    args.iter().skip(1).for_each(|branch| self.check_expression(branch, is_contract));
+                    self.guarded = guarded;
+                    self.is_authorized = is_authorized;
+                }
+            },
+            Some(NativeFunctions::AsContract) => {
+                args.iter().for_each(|arg| self.check_expression(arg, true));
+            },
+            _ => {
+                args.iter().for_each(|arg| self.check_expression(arg, is_contract));
+                if let Some(native_function) = native_function {
+                    self.check_sink(native_function, args, is_contract);
+                }
+            }
+        }
+    }
+
+    fn check_sink(&mut self, function: NativeFunctions, args: &[SymbolicExpression], is_contract: bool) {
+        let sinks: &[(usize, &str)] = match function {
+            // (stx-transfer? amount sender recipient)
+            NativeFunctions::StxTransfer if is_contract => &[(0, "the amount"), (1, "the sender"), (2, "the recipient")],
+            NativeFunctions::StxTransfer => &[(0, "the amount")],
+            // (ft-mint? token amount recipient)
+            NativeFunctions::MintToken => &[(1, "the amount")],
+            // (ft-transfer? token amount sender recipient)
+            NativeFunctions::TransferToken | NativeFunctions::TransferAsset if is_contract =>
+                &[(2, "the sender"), (3, "the recipient")],
+            // (map-set map key value), (map-delete map key)
+            NativeFunctions::SetEntry | NativeFunctions::DeleteEntry => &[(1, "the key")],
+            // (var-set var value)
+            NativeFunctions::SetVar => &[(1, "the value")],
+            _ => return
+        };
+        for (index, role) in sinks.iter() {
+            let arg = match args.get(*index) {
+                Some(arg) => arg,
+                None => continue
+            };
+            if let Some(taint) = self.taint(arg) {
+                let sink = format!("{} of {}", role, function.get_name());
+                let mut warning = CheckWarning::new(CheckWarnings::UntrustedArgument(taint.argument.to_string(), sink), vec![arg.clone()]);
+                for (span, message) in taint.path.into_iter() {
+                    warning.diagnostic.add_related_information(span, message);
+                }
+                self.warnings.push(warning);
+            }
+        }
+    }
+
+    fn bind(&mut self, name: &SymbolicExpression, value: &SymbolicExpression) {
+        let atom = match name.match_atom() {
+            Some(atom) => atom,
+            None => return
+        };
+        let taint = self.taint(value).map(|mut taint| {
+            taint.path.push((name.span.clone(), format!("'{}' is computed from it", atom.as_str())));
+            taint
+        });
+        self.bindings.push(Binding { name: atom.clone(), taint });
+    }
+
+    /// Marks the names read by the condition as checked.
+    fn guard(&mut self, condition: &SymbolicExpression) {
+        if self.authorizes(condition) {
+            self.is_authorized = true;
+        }
+        self.guard_names(condition);
+    }
+
+    fn guard_names(&mut self, condition: &SymbolicExpression) {
+        match condition.expr {
+            Atom(ref name) => {
+                self.guarded.insert(name.clone());
+            },
+            List(ref list) => read_items(list).into_iter().for_each(|item| self.guard_names(item)),
+            _ => {}
+        }
+    }
+
+    /// Whether the condition holds only for an authorized caller: `(is-eq tx-sender owner)`,
+    /// alone or in an `and`, with `owner` a constant, a data var or a principal literal.
+    fn authorizes(&self, condition: &SymbolicExpression) -> bool {
+        let (function, args) = match condition.match_list().and_then(|list| list.split_first()) {
+            Some(split) => split,
+            None => return false
+        };
+        match function.match_atom().and_then(|name| NativeFunctions::lookup_by_name(name)) {
+            Some(NativeFunctions::And) => args.iter().any(|arg| self.authorizes(arg)),
+            Some(NativeFunctions::Equals) if args.len() == 2 => {
+                let is_sender = |arg: &SymbolicExpression| matches!(arg.match_atom().and_then(|name| NativeVariables::lookup_by_name(name)),
+                                                                     Some(NativeVariables::TxSender) | Some(NativeVariables::ContractCaller));
+                (is_sender(&args[0]) && self.is_trusted(&args[1])) || (is_sender(&args[1]) && self.is_trusted(&args[0]))
+            },
+            _ => false
+        }
+    }
+
+    /// Whether the value is set by the contract, not by the caller.
+    fn is_trusted(&self, exp: &SymbolicExpression) -> bool {
+        match exp.expr {
+            Atom(ref name) => self.constants.contains(name),
+            AtomValue(Value::Principal(_)) | LiteralValue(Value::Principal(_)) => true,
+            List(ref list) => matches!(list.first().and_then(|function| function.match_atom()).and_then(|name| NativeFunctions::lookup_by_name(name)),
+                                       Some(NativeFunctions::FetchVar)),
+            _ => false
+        }
+    }
+
+    /// The unchecked argument the value is computed from, if any.
+    fn taint(&self, exp: &SymbolicExpression) -> Option<Taint> {
+        if self.is_authorized {
+            return None;
+        }
+        match exp.expr {
+            Atom(ref name) => {
+                if self.guarded.contains(name) {
+                    return None;
+                }
+                let taint = self.bindings.iter().rev().find(|binding| &binding.name == name)?.taint.as_ref()?;
+                if self.guarded.contains(&taint.argument) {
+                    return None;
+                }
+                Some(taint.clone())
+            },
+            List(ref list) => read_items(list).into_iter().find_map(|item| self.taint(item)),
+            _ => None
+        }
+    }
+}
+
+/// Items of the list read as values: the field names of the tuples are left out.
+fn read_items(list: &[SymbolicExpression]) -> Vec<&SymbolicExpression> {
+    let function = list.first().and_then(|function| function.match_atom()).and_then(|name| NativeFunctions::lookup_by_name(name));
+    match function {
+        // (get key tuple)
+        Some(NativeFunctions::TupleGet) => list.iter().skip(2).collect(),
+        // (tuple (key value)...)
+        Some(NativeFunctions::TupleCons) => list.iter().skip(1)
+            .filter_map(|pair| pair.match_list())
+            .flat_map(|pair| pair.iter().skip(1))
+            .collect(),
+        _ => list.iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::clarity::ast;
+    use crate::clarity::types::QualifiedContractIdentifier;
+
+    fn warnings(contract: &str) -> Vec<CheckWarning> {
+        let contract_identifier = QualifiedContractIdentifier::transient();
+        let contract_ast = ast::build_ast(&contract_identifier, contract, &mut ()).unwrap();
+        TaintChecker::new().run(&contract_ast.expressions)
+    }
+
+    #[test]
+    fn test_unchecked_arguments_reaching_sinks() {
+        let contract = "(define-data-var admin principal tx-sender)
+                        (define-map owners ((id uint)) ((owner principal)))
+                        (define-public (set-admin (value principal))
+                            (ok (var-set admin value)))
+                        (define-public (claim (id uint) (amount uint))
+                            (let ((fee (/ amount u100)))
+                                (map-set owners {id: id} {owner: tx-sender})
+                                (map-set owners {id: u1} {owner: tx-sender})
+                                (asserts! (< amount u1000) (err u1))
+                                (if (> (get id {id: u10}) u10) (map-delete owners {id: id}) false)
+                                (if (> id u10) (map-delete owners {id: id}) false)
+                                (stx-transfer? fee tx-sender (as-contract tx-sender))))
+                        (define-public (withdraw (recipient principal))
+                            (begin
+                                (asserts! (is-eq tx-sender (var-get admin)) (err u1))
+                                (as-contract (stx-transfer? u1 tx-sender recipient))))";
+        let found: Vec<_> = warnings(contract).into_iter()
+            .map(|warning| (warning.warning, warning.diagnostic.spans[0].start_line))
+            .collect();
+        assert_eq!(found, vec![
+            (CheckWarnings::UntrustedArgument("value".to_string(), "the value of var-set".to_string()), 4),
+            (CheckWarnings::UntrustedArgument("id".to_string(), "the key of map-set".to_string()), 7),
+            (CheckWarnings::UntrustedArgument("id".to_string(), "the key of map-delete".to_string()), 10),
+        ]);
+    }
+
+    #[test]
+    fn test_reading_the_sender_does_not_authorize() {
+        let contract = "(define-constant owner tx-sender)
+                        (define-map owners ((id uint)) ((owner principal)))
+                        (define-public (buy (id uint) (amount uint))
+                            (begin
+                                (asserts! (>= (stx-get-balance tx-sender) amount) (err u1))
+                                (asserts! (is-eq tx-sender contract-caller) (err u2))
+                                (map-set owners {id: id} {owner: tx-sender})
+                                (ok true)))
+                        (define-public (grant (id uint))
+                            (begin
+                                (asserts! (and (> id u0) (is-eq contract-caller owner)) (err u1))
+                                (ok (map-set owners {id: id} {owner: tx-sender}))))";
+        let found: Vec<_> = warnings(contract).into_iter()
+            .map(|warning| (warning.warning, warning.diagnostic.spans[0].start_line))
+            .collect();
+        assert_eq!(found, vec![
+            (CheckWarnings::UntrustedArgument("id".to_string(), "the key of map-set".to_string()), 7),
+        ]);
+    }
+
+    #[test]
+    fn test_propagation_path_is_related() {
+        let contract = "(define-public (deposit (amount uint))
+                            (let ((total (* amount u2)))
+                                (match (some total) value (stx-transfer? value tx-sender (as-contract tx-sender)) (ok true))))";
+        let found = warnings(contract);
+        assert_eq!(found.len(), 1);
+        let related: Vec<_> = found[0].diagnostic.related_information.iter()
+            .map(|related| related.message.as_str())
+            .collect();
+        assert_eq!(related, vec![
+            "'amount' is an argument of a public function",
+            "'total' is computed from it",
+            "'value' is computed from it",
+        ]);
+    }
+}
@@ -19,6 +19,11 @@ impl AnalysisPass for TraitChecker {
         command.run(contract_analysis, analysis_db)?;
         Ok(())
     }
+
+    fn run_pass_collecting_errors(contract_analysis: &mut ContractAnalysis, analysis_db: &mut AnalysisDatabase) -> Vec<CheckError> {
+        let mut command = TraitChecker::new();
+        command.run_collecting_errors(contract_analysis, analysis_db)
+    }
 }
 
 impl TraitChecker {
@@ -31,16 +36,40 @@ impl TraitChecker {
     pub fn run(&mut self, contract_analysis: &mut ContractAnalysis, analysis_db: &mut AnalysisDatabase) -> CheckResult<()> {
     
         for trait_identifier in &contract_analysis.implemented_traits {
-
-            let trait_name = trait_identifier.name.to_string();
-            let contract_defining_trait = analysis_db.load_contract(&trait_identifier.contract_identifier)
-                .ok_or(CheckErrors::TraitReferenceUnknown(trait_identifier.name.to_string()))?;
-            
-            let trait_definition = contract_defining_trait.get_defined_trait(&trait_name)
-                .ok_or(CheckErrors::TraitReferenceUnknown(trait_identifier.name.to_string()))?;
-
-            contract_analysis.check_trait_compliance(trait_identifier, trait_definition)?;
+            self.check_implemented_trait(trait_identifier, contract_analysis, analysis_db)?;
         }
         Ok(())
     }
+
+    pub fn run_collecting_errors(&mut self, contract_analysis: &mut ContractAnalysis, analysis_db: &mut AnalysisDatabase) -> Vec<CheckError> {
+        contract_analysis.implemented_traits.iter()
+            .filter_map(|trait_identifier| {
+                let mut error = self.check_implemented_trait(trait_identifier, contract_analysis, analysis_db).err()?;
+                if let Some(exp) = find_impl_trait_expression(trait_identifier, &contract_analysis.expressions) {
+                    error.set_expression(exp);
+                }
+                Some(error)
+            })
+            .collect()
+    }
+
+    fn check_implemented_trait(&self, trait_identifier: &TraitIdentifier, contract_analysis: &ContractAnalysis, analysis_db: &mut AnalysisDatabase) -> CheckResult<()> {
+        let trait_name = trait_identifier.name.to_string();
+        let contract_defining_trait = analysis_db.load_contract(&trait_identifier.contract_identifier)
+            .ok_or(CheckErrors::TraitReferenceUnknown(trait_identifier.name.to_string()))?;
+        
+        let trait_definition = contract_defining_trait.get_defined_trait(&trait_name)
+            .ok_or(CheckErrors::TraitReferenceUnknown(trait_identifier.name.to_string()))?;
+
+        contract_analysis.check_trait_compliance(trait_identifier, trait_definition)
+    }
+}
+
+fn find_impl_trait_expression<'a>(trait_identifier: &TraitIdentifier, expressions: &'a [SymbolicExpression]) -> Option<&'a SymbolicExpression> {
+    expressions.iter().find(|exp| {
+        match DefineFunctionsParsed::try_parse(exp) {
+            Ok(Some(DefineFunctionsParsed::ImplTrait { trait_identifier: implemented })) => implemented == trait_identifier,
+            _ => false
+        }
+    })
 }
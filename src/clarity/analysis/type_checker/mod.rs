@@ -13,7 +13,7 @@ use crate::clarity::functions::{NativeFunctions, DefineFunctionsParsed, NativeVa
 use crate::clarity::costs::{CostTracker, ExecutionCost, LimitedCostTracker, CostErrors,
                 cost_functions, analysis_typecheck_cost, CostOverflowingMath};
 use crate::clarity::analysis::AnalysisDatabase;
-pub use crate::clarity::analysis::types::{ContractAnalysis, AnalysisPass};
+pub use crate::clarity::analysis::types::{ContractAnalysis, AnalysisPass, FailedDefinitions};
 
 use self::contexts::{TypeMap, TypingContext, ContractContext};
 
@@ -80,6 +80,17 @@ impl AnalysisPass for TypeChecker <'_, '_> {
             },
         }
     }
+
+    fn run_pass_collecting_errors(contract_analysis: &mut ContractAnalysis, analysis_db: &mut AnalysisDatabase) -> Vec<CheckError> {
+        let cost_track = contract_analysis.take_contract_cost_tracker();
+        let mut command = TypeChecker::new(analysis_db, cost_track);
+        // the definitions that did check are kept, so that the analysis can
+        //   still be used by the caller.
+        let errors = command.run_collecting_errors(contract_analysis);
+        let cost_track = command.into_contract_analysis(contract_analysis);
+        contract_analysis.replace_contract_cost_tracker(cost_track);
+        errors
+    }
 }
 
 pub type TypeResult = CheckResult<TypeSignature>;
@@ -235,6 +246,39 @@ impl <'a, 'b> TypeChecker <'a, 'b> {
     }
 
     pub fn run(&mut self, contract_analysis: &mut ContractAnalysis) -> CheckResult<()> {
+        self.charge_analysis_storage(contract_analysis)?;
+
+        let mut local_context = TypingContext::new();
+
+        for exp in contract_analysis.expressions.iter() {
+            self.type_check_top_level_expression(exp, &mut local_context)?;
+        }
+        Ok(())
+    }
+
+    pub fn run_collecting_errors(&mut self, contract_analysis: &mut ContractAnalysis) -> Vec<CheckError> {
+        if let Err(error) = self.charge_analysis_storage(contract_analysis) {
+            return vec![error];
+        }
+
+        let mut errors = vec![];
+        let mut failed_definitions = FailedDefinitions::new();
+        let mut local_context = TypingContext::new();
+
+        for exp in contract_analysis.expressions.iter() {
+            if let Err(error) = self.type_check_top_level_expression(exp, &mut local_context) {
+                // an error raised while checking a function body can leave the return tracker dirty.
+                self.function_return_tracker = None;
+                if !failed_definitions.caused(&error.err) {
+                    errors.push(error);
+                }
+                failed_definitions.record(exp);
+            }
+        }
+        errors
+    }
+
+    fn charge_analysis_storage(&mut self, contract_analysis: &ContractAnalysis) -> CheckResult<()> {
         // charge for the eventual storage cost of the analysis --
         //  it is linear in the size of the AST.
         let mut size: u64 = 0;
@@ -251,22 +295,21 @@ impl <'a, 'b> TypeChecker <'a, 'b> {
         }
 
         runtime_cost!(cost_functions::ANALYSIS_STORAGE, self, size)?;
+        Ok(())
+    }
 
-        let mut local_context = TypingContext::new();
-
-        for exp in contract_analysis.expressions.iter() {
-            let mut result_res = self.try_type_check_define(&exp, &mut local_context);
-            if let Err(ref mut error) = result_res {
-                if !error.has_expression() {
-                    error.set_expression(&exp);
-                }
-            }
-            let result = result_res?;
-            if result.is_none() {
-                // was _not_ a define statement, so handle like a normal statement.
-                self.type_check(&exp, &local_context)?;
+    fn type_check_top_level_expression(&mut self, exp: &SymbolicExpression, local_context: &mut TypingContext) -> CheckResult<()> {
+        let mut result_res = self.try_type_check_define(exp, local_context);
+        if let Err(ref mut error) = result_res {
+            if !error.has_expression() {
+                error.set_expression(exp);
             }
         }
+        let result = result_res?;
+        if result.is_none() {
+            // was _not_ a define statement, so handle like a normal statement.
+            self.type_check(exp, local_context)?;
+        }
         Ok(())
     }
 
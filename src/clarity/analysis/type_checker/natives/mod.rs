@@ -206,7 +206,12 @@ fn check_special_if(checker: &mut TypeChecker, args: &[SymbolicExpression], cont
     analysis_typecheck_cost(checker, expr1, expr2)?;
 
     TypeSignature::least_supertype(expr1, expr2)
-        .map_err(|_| CheckErrors::IfArmsMustMatch(expr1.clone(), expr2.clone()).into())
+        .map_err(|_| {
+            let mut error = CheckError::new(CheckErrors::IfArmsMustMatch(expr1.clone(), expr2.clone()));
+            error.diagnostic.add_related_information(args[1].span.clone(), format!("this branch is of type '{}'", expr1));
+            error.diagnostic.add_related_information(args[2].span.clone(), format!("this branch is of type '{}'", expr2));
+            error
+        })
 }
 
 fn check_contract_call(checker: &mut TypeChecker, args: &[SymbolicExpression], context: &TypingContext) -> TypeResult {
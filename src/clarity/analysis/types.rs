@@ -1,9 +1,11 @@
 use std::collections::{BTreeMap, BTreeSet, HashMap};
 use crate::clarity::{SymbolicExpression, ClarityName};
+use crate::clarity::functions::DefineFunctions;
 use crate::clarity::types::{TypeSignature, FunctionType, QualifiedContractIdentifier, TraitIdentifier};
 use crate::clarity::types::signatures::FunctionSignature;
 use crate::clarity::analysis::analysis_db::{AnalysisDatabase};
-use crate::clarity::analysis::errors::{CheckResult, CheckErrors};
+use crate::clarity::analysis::errors::{CheckResult, CheckError, CheckErrors};
+use crate::clarity::analysis::warnings::CheckWarning;
 use crate::clarity::analysis::contract_interface_builder::ContractInterface;
 use crate::clarity::analysis::type_checker::contexts::TypeMap;
 use serde::{Serialize, Deserialize};
@@ -14,6 +16,52 @@ const SERIALIZE_FAIL_MESSAGE: &str = "PANIC: Failed to deserialize bad database
 
 pub trait AnalysisPass {
     fn run_pass(contract_analysis: &mut ContractAnalysis, analysis_db: &mut AnalysisDatabase) -> CheckResult<()>;
+
+    /// Error-accumulating flavor of `run_pass`: passes able to check top-level
+    /// expressions independently override it to report every error they find.
+    fn run_pass_collecting_errors(contract_analysis: &mut ContractAnalysis, analysis_db: &mut AnalysisDatabase) -> Vec<CheckError> {
+        match Self::run_pass(contract_analysis, analysis_db) {
+            Ok(_) => vec![],
+            Err(e) => vec![e]
+        }
+    }
+}
+
+/// Passes reporting warnings on a contract once analysed. They never fail, and run on
+/// whatever the analysis could make of the contract.
+pub trait LintPass {
+    fn run_lint(contract_analysis: &ContractAnalysis) -> Vec<CheckWarning>;
+}
+
+/// Keeps track of the top-level definitions that failed to check, so that the errors
+/// caused by their absence are not reported a second time on each of their dependents.
+pub struct FailedDefinitions {
+    names: BTreeSet<ClarityName>
+}
+
+impl FailedDefinitions {
+    pub fn new() -> FailedDefinitions {
+        FailedDefinitions { names: BTreeSet::new() }
+    }
+
+    pub fn record(&mut self, expression: &SymbolicExpression) {
+        if let Some(name) = DefineFunctions::try_parse_defined_name(expression) {
+            self.names.insert(name.clone());
+        }
+    }
+
+    /// Checks whether the error is about the absence of a definition that failed to
+    /// check. Other errors of the dependents are still reported.
+    pub fn caused(&self, error: &CheckErrors) -> bool {
+        let name = match error {
+            CheckErrors::UndefinedFunction(name) | CheckErrors::UndefinedVariable(name)
+            | CheckErrors::UnknownFunction(name) | CheckErrors::IllegalOrUnknownFunctionApplication(name)
+            | CheckErrors::NoSuchDataVariable(name) | CheckErrors::NoSuchMap(name)
+            | CheckErrors::NoSuchFT(name) | CheckErrors::NoSuchNFT(name) => name,
+            _ => return false
+        };
+        self.names.iter().any(|failed| failed.as_str() == name)
+    }
 }
 
 #[derive(Debug, Serialize, Clone, PartialEq)]
@@ -0,0 +1,136 @@
+use crate::clarity::analysis::types::{ContractAnalysis, LintPass};
+use crate::clarity::analysis::type_checker::contexts::TypeMap;
+use crate::clarity::analysis::warnings::{CheckWarning, CheckWarnings};
+use crate::clarity::representations::SymbolicExpression;
+use crate::clarity::types::TypeSignature;
+use crate::clarity::functions::{DefineFunctions, NativeFunctions};
+
+/// Reports the responses whose value is dropped, e.g. the result of a transfer in the
+/// middle of a `begin`: the transfer may fail, and the function carry on regardless.
+pub struct UncheckedResponseChecker <'a> {
+    type_map: Option<&'a TypeMap>,
+    warnings: Vec<CheckWarning>,
+}
+
+impl <'a> LintPass for UncheckedResponseChecker <'a> {
+
+    fn run_lint(contract_analysis: &ContractAnalysis) -> Vec<CheckWarning> {
+        let mut command = UncheckedResponseChecker::new(contract_analysis.type_map.as_ref());
+        command.run(&contract_analysis.expressions)
+    }
+}
+
+impl <'a> UncheckedResponseChecker <'a> {
+
+    fn new(type_map: Option<&'a TypeMap>) -> Self {
+        Self {
+            type_map,
+            warnings: vec![],
+        }
+    }
+
+    pub fn run(&mut self, expressions: &[SymbolicExpression]) -> Vec<CheckWarning> {
+        for exp in expressions.iter() {
+            // top-level statements run once, when the contract is deployed.
+            if DefineFunctions::try_parse(exp).is_none() {
+                self.check_dropped(exp);
+            }
+            self.check_expression(exp);
+        }
+        std::mem::take(&mut self.warnings)
+    }
+
+    fn check_expression(&mut self, exp: &SymbolicExpression) {
+        let list = match exp.match_list() {
+            Some(list) => list,
+            None => return
+        };
+        let statements = match native_function(exp) {
+            // (begin statement... value)
+            Some(NativeFunctions::Begin) => list.get(1..list.len().saturating_sub(1)),
+            // (let (bindings...) statement... value)
+            Some(NativeFunctions::Let) => list.get(2..list.len().saturating_sub(1)),
+            _ => None
+        };
+        for statement in statements.unwrap_or_default() {
+            self.check_dropped(statement);
+        }
+        for item in list.iter() {
+            self.check_expression(item);
+        }
+    }
+
+    /// Reports the expression when its value tells whether it failed.
+    fn check_dropped(&mut self, exp: &SymbolicExpression) {
+        let warning = if self.is_response(exp) {
+            CheckWarnings::UncheckedResponse
+        } else {
+            match native_function(exp) {
+                Some(function @ NativeFunctions::InsertEntry) | Some(function @ NativeFunctions::DeleteEntry) =>
+                    CheckWarnings::IgnoredResult(function.get_name()),
+                _ => return
+            }
+        };
+        self.warnings.push(CheckWarning::new(warning, vec![exp.clone()]));
+    }
+
+    fn is_response(&self, exp: &SymbolicExpression) -> bool {
+        if let Some(type_signature) = self.type_map.and_then(|type_map| type_map.get_type(exp)) {
+            return matches!(type_signature, TypeSignature::ResponseType(_));
+        }
+        // expressions are left untyped in the definitions failing to check.
+        matches!(native_function(exp),
+                 Some(NativeFunctions::StxTransfer) | Some(NativeFunctions::StxBurn)
+                 | Some(NativeFunctions::TransferToken) | Some(NativeFunctions::TransferAsset)
+                 | Some(NativeFunctions::MintToken) | Some(NativeFunctions::MintAsset)
+                 | Some(NativeFunctions::ContractCall))
+    }
+}
+
+fn native_function(exp: &SymbolicExpression) -> Option<NativeFunctions> {
+    let function = exp.match_list()?.first()?.match_atom()?;
+    NativeFunctions::lookup_by_name(function)
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::clarity::analysis::{self, AnalysisDatabase};
+    use crate::clarity::ast;
+    use crate::clarity::costs::LimitedCostTracker;
+    use crate::clarity::types::QualifiedContractIdentifier;
+
+    fn warnings(contract: &str) -> Vec<(CheckWarnings, u32)> {
+        let contract_identifier = QualifiedContractIdentifier::transient();
+        let mut contract_ast = ast::build_ast(&contract_identifier, contract, &mut ()).unwrap();
+        let mut db = AnalysisDatabase::new();
+        let (contract_analysis, _) = analysis::run_analysis_collecting_errors(
+            &contract_identifier, &mut contract_ast.expressions, &mut db, false, LimitedCostTracker::new_max_limit());
+        UncheckedResponseChecker::run_lint(&contract_analysis).into_iter()
+            .map(|warning| (warning.warning, warning.diagnostic.spans[0].start_line))
+            .collect()
+    }
+
+    #[test]
+    fn test_dropped_responses() {
+        let contract = "(define-fungible-token token)
+                        (define-map claimed ((who principal)) ((done bool)))
+                        (define-private (pay (amount uint)) (stx-transfer? amount tx-sender 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR))
+                        (define-public (claim (amount uint))
+                            (begin
+                                (map-insert claimed {who: tx-sender} {done: true})
+                                (pay amount)
+                                (try! (ft-mint? token amount tx-sender))
+                                (map-set claimed {who: tx-sender} {done: true})
+                                (let ((paid (pay amount)))
+                                    (ft-transfer? token amount tx-sender tx-sender)
+                                    paid)))
+                        (ft-mint? token u10 tx-sender)";
+        assert_eq!(warnings(contract), vec![
+            (CheckWarnings::IgnoredResult("map-insert".to_string()), 6),
+            (CheckWarnings::UncheckedResponse, 7),
+            (CheckWarnings::UncheckedResponse, 11),
+            (CheckWarnings::UncheckedResponse, 13),
+        ]);
+    }
+}
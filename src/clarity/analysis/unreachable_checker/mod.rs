@@ -0,0 +1,261 @@
+use std::collections::{HashMap, HashSet};
+use crate::clarity::analysis::types::{ContractAnalysis, LintPass};
+use crate::clarity::analysis::warnings::{CheckWarning, CheckWarnings};
+use crate::clarity::representations::SymbolicExpression;
+use crate::clarity::types::{OptionalData, Value};
+use crate::clarity::functions::{DefineFunctions, NativeFunctions, NativeVariables};
+
+/// Reports the code made dead by values written in the contract: conditions which are
+/// `true` or `false`, matches on `none`, unwraps of `(ok ...)` which always succeed, and
+/// the statements following an expression which always aborts, e.g. `(unwrap-panic none)`.
+pub struct UnreachableChecker {
+    /// Ids of the expressions whose value is returned by their function.
+    tails: HashSet<u64>,
+    /// Ids of the `(asserts! false ...)` found in a sequence, with the last expression
+    /// of the sequence, followed by the sequence when it is returned by its function.
+    sequences: HashMap<u64, Vec<SymbolicExpression>>,
+    warnings: Vec<CheckWarning>,
+}
+
+/// What is known of a value from the way it is written.
+enum Literal<'a> {
+    Bool(bool),
+    None,
+    Some(&'a SymbolicExpression),
+    Ok(&'a SymbolicExpression),
+    Err(&'a SymbolicExpression),
+}
+
+impl LintPass for UnreachableChecker {
+
+    fn run_lint(contract_analysis: &ContractAnalysis) -> Vec<CheckWarning> {
+        let mut command = UnreachableChecker::new();
+        command.run(&contract_analysis.expressions)
+    }
+}
+
+impl UnreachableChecker {
+
+    fn new() -> Self {
+        Self {
+            tails: HashSet::new(),
+            sequences: HashMap::new(),
+            warnings: vec![],
+        }
+    }
+
+    pub fn run(&mut self, expressions: &[SymbolicExpression]) -> Vec<CheckWarning> {
+        for exp in expressions.iter() {
+            match DefineFunctions::try_parse(exp) {
+                Some((DefineFunctions::PrivateFunction, args)) | Some((DefineFunctions::PublicFunction, args))
+                | Some((DefineFunctions::ReadOnlyFunction, args)) => {
+                    if let Some(body) = args.get(1) {
+                        self.tails.insert(body.id);
+                    }
+                },
+                _ => {}
+            }
+            self.check_expression(exp);
+        }
+        std::mem::take(&mut self.warnings)
+    }
+
+    fn check_expression(&mut self, exp: &SymbolicExpression) {
+        let list = match exp.match_list() {
+            Some(list) => list,
+            None => return
+        };
+        let (function, args) = match list.split_first() {
+            Some(split) => split,
+            None => return
+        };
+        match function.match_atom().and_then(|name| NativeFunctions::lookup_by_name(name)) {
+            // (begin statement... value)
+            Some(NativeFunctions::Begin) => self.check_sequence(exp, args),
+            // (let (bindings...) statement... value)
+            Some(NativeFunctions::Let) => self.check_sequence(exp, args.get(1..).unwrap_or_default()),
+            Some(NativeFunctions::If) if args.len() == 3 => {
+                if let Some(Literal::Bool(value)) = literal(&args[0]) {
+                    self.warnings.push(CheckWarning::new(CheckWarnings::ConstantCondition(value), vec![args[0].clone(), exp.clone()]));
+                }
+            },
+            Some(NativeFunctions::Asserts) if args.len() == 2 => {
+                if let Some(Literal::Bool(value)) = literal(&args[0]) {
+                    let mut expressions = vec![args[0].clone(), exp.clone()];
+                    expressions.extend(self.sequences.remove(&exp.id).unwrap_or_default());
+                    self.warnings.push(CheckWarning::new(CheckWarnings::ConstantCondition(value), expressions));
+                }
+            },
+            // (match input some-name some-branch none-branch)
+            Some(NativeFunctions::Match) if args.len() == 4 => {
+                if let Some(Literal::None) = literal(&args[0]) {
+                    self.warnings.push(CheckWarning::new(CheckWarnings::ConstantMatch, vec![args[0].clone(), exp.clone()]));
+                }
+            },
+            Some(function @ NativeFunctions::TryRet) | Some(function @ NativeFunctions::Unwrap)
+            | Some(function @ NativeFunctions::UnwrapRet) => {
+                if let Some(Literal::Ok(_)) | Some(Literal::Some(_)) = args.first().and_then(literal) {
+                    self.warnings.push(CheckWarning::new(CheckWarnings::RedundantUnwrap(function.get_name()), vec![exp.clone()]));
+                }
+            },
+            Some(function @ NativeFunctions::UnwrapErr) | Some(function @ NativeFunctions::UnwrapErrRet) => {
+                if let Some(Literal::Err(_)) = args.first().and_then(literal) {
+                    self.warnings.push(CheckWarning::new(CheckWarnings::RedundantUnwrap(function.get_name()), vec![exp.clone()]));
+                }
+            },
+            _ => {}
+        }
+        list.iter().for_each(|item| self.check_expression(item));
+    }
+
+    /// Notes the rest of the sequence following its first `(asserts! false thrown)`,
+    /// reported along with the assertion, or else reports the statements following one
+    /// which always aborts. The last expression is then left alone: it gives the
+    /// sequence its type.
+    fn check_sequence(&mut self, sequence: &SymbolicExpression, statements: &[SymbolicExpression]) {
+        let is_tail = self.tails.contains(&sequence.id);
+        if let (true, Some(last)) = (is_tail, statements.last()) {
+            self.tails.insert(last.id);
+        }
+        let assertion = statements.iter().find(|statement| is_failed_assertion(statement));
+        if let (Some(assertion), Some(last)) = (assertion, statements.last()) {
+            let mut rest = vec![last.clone()];
+            if is_tail {
+                rest.push(sequence.clone());
+            }
+            self.sequences.insert(assertion.id, rest);
+            return;
+        }
+
+        let statements = match statements.split_last() {
+            Some((_, statements)) => statements,
+            None => return
+        };
+        let abort = match statements.iter().position(always_aborts) {
+            Some(abort) => abort,
+            None => return
+        };
+        if let (Some(first), Some(last)) = (statements.get(abort + 1), statements.last()) {
+            let expressions = vec![first.clone(), statements[abort].clone(), last.clone()];
+            self.warnings.push(CheckWarning::new(CheckWarnings::UnreachableCode, expressions));
+        }
+    }
+}
+
+fn literal(exp: &SymbolicExpression) -> Option<Literal<'_>> {
+    if let Some(value) = exp.match_atom_value().or_else(|| exp.match_literal_value()) {
+        return match value {
+            Value::Bool(value) => Some(Literal::Bool(*value)),
+            Value::Optional(OptionalData { data: None }) => Some(Literal::None),
+            _ => None
+        };
+    }
+    if let Some(name) = exp.match_atom() {
+        return match NativeVariables::lookup_by_name(name) {
+            Some(NativeVariables::NativeTrue) => Some(Literal::Bool(true)),
+            Some(NativeVariables::NativeFalse) => Some(Literal::Bool(false)),
+            Some(NativeVariables::NativeNone) => Some(Literal::None),
+            _ => None
+        };
+    }
+    let list = exp.match_list()?;
+    let value = match list {
+        [_, value] => value,
+        _ => return None
+    };
+    match NativeFunctions::lookup_by_name(list[0].match_atom()?)? {
+        NativeFunctions::ConsSome => Some(Literal::Some(value)),
+        NativeFunctions::ConsOkay => Some(Literal::Ok(value)),
+        NativeFunctions::ConsError => Some(Literal::Err(value)),
+        _ => None
+    }
+}
+
+/// Whether the expression is `(asserts! false thrown)`.
+fn is_failed_assertion(exp: &SymbolicExpression) -> bool {
+    match exp.match_list() {
+        Some([function, condition, _]) => matches!(function.match_atom().and_then(|name| NativeFunctions::lookup_by_name(name)), Some(NativeFunctions::Asserts))
+            && matches!(literal(condition), Some(Literal::Bool(false))),
+        _ => false
+    }
+}
+
+/// Whether the expression aborts whatever the state, e.g. `(unwrap-panic none)`.
+fn always_aborts(exp: &SymbolicExpression) -> bool {
+    let list = match exp.match_list() {
+        Some(list) if !list.is_empty() => list,
+        _ => return false
+    };
+    let input = match list.get(1).and_then(literal) {
+        Some(input) => input,
+        None => return false
+    };
+    match list[0].match_atom().and_then(|name| NativeFunctions::lookup_by_name(name)) {
+        Some(NativeFunctions::TryRet) | Some(NativeFunctions::Unwrap) | Some(NativeFunctions::UnwrapRet) =>
+            matches!(input, Literal::None | Literal::Err(_)),
+        Some(NativeFunctions::UnwrapErr) | Some(NativeFunctions::UnwrapErrRet) => matches!(input, Literal::Ok(_)),
+        _ => false
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::clarity::ast;
+    use crate::clarity::types::QualifiedContractIdentifier;
+
+    fn warnings(contract: &str) -> Vec<(CheckWarnings, u32)> {
+        let contract_identifier = QualifiedContractIdentifier::transient();
+        let contract_ast = ast::build_ast(&contract_identifier, contract, &mut ()).unwrap();
+        UnreachableChecker::new().run(&contract_ast.expressions).into_iter()
+            .map(|warning| (warning.warning, warning.diagnostic.spans[0].start_line))
+            .collect()
+    }
+
+    #[test]
+    fn test_constant_conditions() {
+        let contract = "(define-read-only (f (a uint))
+                            (begin
+                                (asserts! true (err u1))
+                                (asserts! (> a u1) (err u1))
+                                (if false (ok u1) (ok a))))
+                        (define-read-only (g)
+                            (match none value (ok value) (err u1)))
+                        (define-read-only (h (a uint))
+                            (if (> a u1) (asserts! false (err u1)) true))
+                        (define-read-only (k)
+                            (asserts! false (err u1)))";
+        assert_eq!(warnings(contract), vec![
+            (CheckWarnings::ConstantCondition(true), 3),
+            (CheckWarnings::ConstantCondition(false), 5),
+            (CheckWarnings::ConstantMatch, 7),
+            (CheckWarnings::ConstantCondition(false), 9),
+            (CheckWarnings::ConstantCondition(false), 11),
+        ]);
+    }
+
+    #[test]
+    fn test_unreachable_code() {
+        let contract = "(define-public (f (a uint))
+                            (begin
+                                (asserts! false (err u1))
+                                (print a)
+                                (print a)
+                                (ok a)))
+                        (define-public (g (a uint))
+                            (let ((b (try! (ok a))))
+                                (unwrap-panic (err u1))
+                                (print b)
+                                (ok b)))
+                        (define-public (h)
+                            (begin
+                                (asserts! false (err u1))
+                                (ok true)))";
+        assert_eq!(warnings(contract), vec![
+            (CheckWarnings::ConstantCondition(false), 3),
+            (CheckWarnings::UnreachableCode, 10),
+            (CheckWarnings::RedundantUnwrap("try!".to_string()), 8),
+            (CheckWarnings::ConstantCondition(false), 14),
+        ]);
+    }
+}
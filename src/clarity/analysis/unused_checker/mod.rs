@@ -0,0 +1,268 @@
+use std::collections::HashSet;
+
+use crate::clarity::analysis::types::{ContractAnalysis, LintPass};
+use crate::clarity::analysis::warnings::{CheckWarning, CheckWarnings};
+use crate::clarity::representations::{SymbolicExpression, ClarityName};
+use crate::clarity::representations::SymbolicExpressionType::{Atom, List};
+use crate::clarity::functions::{DefineFunctions, NativeFunctions};
+
+/// Bindings named with a trailing underscore are unused on purpose.
+const UNUSED_MARKER: char = '_';
+
+/// Reports the definitions which are never referenced, and the parameters and local
+/// bindings which are never read. Unused code still costs deployment fees.
+pub struct UnusedChecker {
+    /// Names referenced anywhere in the contract.
+    references: HashSet<ClarityName>,
+    /// Local bindings in scope, innermost last.
+    bindings: Vec<Binding>,
+    warnings: Vec<CheckWarning>,
+}
+
+struct Binding {
+    warning: CheckWarnings,
+    name: ClarityName,
+    /// The name, followed by the expressions enclosing it that a fix needs.
+    expressions: Vec<SymbolicExpression>,
+    is_used: bool,
+}
+
+impl LintPass for UnusedChecker {
+
+    fn run_lint(contract_analysis: &ContractAnalysis) -> Vec<CheckWarning> {
+        let mut command = UnusedChecker::new();
+        command.run(&contract_analysis.expressions)
+    }
+}
+
+impl UnusedChecker {
+
+    fn new() -> Self {
+        Self {
+            references: HashSet::new(),
+            bindings: vec![],
+            warnings: vec![],
+        }
+    }
+
+    pub fn run(&mut self, expressions: &[SymbolicExpression]) -> Vec<CheckWarning> {
+        for exp in expressions.iter() {
+            self.check_top_level_expression(exp);
+        }
+
+        for exp in expressions.iter() {
+            let (define_type, args) = match DefineFunctions::try_parse(exp) {
+                Some(define) => define,
+                None => continue
+            };
+            let name = match define_type {
+                DefineFunctions::PrivateFunction =>
+                    args.first().and_then(|signature| signature.match_list()).and_then(|signature| signature.first()),
+                _ => args.first()
+            };
+            let name = match name {
+                Some(name) => name,
+                None => continue
+            };
+            let atom = match name.match_atom() {
+                Some(atom) if !self.references.contains(atom) => atom.to_string(),
+                _ => continue
+            };
+            let warning = match define_type {
+                DefineFunctions::PrivateFunction => CheckWarnings::UnusedPrivateFunction(atom),
+                DefineFunctions::Constant => CheckWarnings::UnusedConstant(atom),
+                DefineFunctions::Map => CheckWarnings::UnusedMap(atom),
+                DefineFunctions::PersistedVariable => CheckWarnings::UnusedDataVar(atom),
+                DefineFunctions::FungibleToken | DefineFunctions::NonFungibleToken => CheckWarnings::UnusedToken(atom),
+                // public functions are entry points, traits are interfaces.
+                _ => continue
+            };
+            self.warnings.push(CheckWarning::new(warning, vec![name.clone(), exp.clone()]));
+        }
+
+        std::mem::take(&mut self.warnings)
+    }
+
+    fn check_top_level_expression(&mut self, exp: &SymbolicExpression) {
+        let (define_type, args) = match DefineFunctions::try_parse(exp) {
+            Some(define) => define,
+            None => return self.check_expression(exp)
+        };
+        match define_type {
+            DefineFunctions::PrivateFunction | DefineFunctions::PublicFunction | DefineFunctions::ReadOnlyFunction => {
+                let signature = args.first().and_then(|signature| signature.match_list()).unwrap_or_default();
+                let scope = self.bindings.len();
+                for parameter in signature.iter().skip(1) {
+                    if let Some(name) = parameter.match_list().and_then(|parameter| parameter.first()) {
+                        self.bind(name, CheckWarnings::UnusedParameter, vec![]);
+                    }
+                }
+                for body in args.iter().skip(1) {
+                    self.check_expression(body);
+                }
+                self.unbind(scope);
+            },
+            DefineFunctions::Constant => {
+                args.iter().skip(1).for_each(|value| self.check_expression(value));
+            },
+            DefineFunctions::PersistedVariable => {
+                args.iter().skip(2).for_each(|initial| self.check_expression(initial));
+            },
+            DefineFunctions::FungibleToken => {
+                args.iter().skip(1).for_each(|max_supply| self.check_expression(max_supply));
+            },
+            // the remaining definitions only contain types and names.
+            _ => {}
+        }
+    }
+
+    fn check_expression(&mut self, exp: &SymbolicExpression) {
+        match exp.expr {
+            Atom(ref name) => self.reference(name),
+            List(ref list) => self.check_list(exp, list),
+            _ => {}
+        }
+    }
+
+    fn check_list(&mut self, exp: &SymbolicExpression, list: &[SymbolicExpression]) {
+        let (function, args) = match list.split_first() {
+            Some(split) => split,
+            None => return
+        };
+        let native_function = function.match_atom().and_then(|name| NativeFunctions::lookup_by_name(name));
+        match native_function {
+            Some(NativeFunctions::Let) => {
+                let scope = self.bindings.len();
+                let bindings = args.first();
+                let pairs: Vec<_> = bindings.and_then(|bindings| bindings.match_list()).unwrap_or_default().iter()
+                    .filter_map(|binding| binding.match_list().map(|pair| (binding, pair)))
+                    .collect();
+                // the bindings are only in scope in the body: their values are computed
+                // before any of them is bound.
+                for (_, pair) in pairs.iter() {
+                    pair.iter().skip(1).for_each(|value| self.check_expression(value));
+                }
+                for (binding, pair) in pairs.iter() {
+                    if let Some(name) = pair.first() {
+                        let context = vec![(*binding).clone(), bindings.unwrap().clone()];
+                        self.bind(name, CheckWarnings::UnusedLetBinding, context);
+                    }
+                }
+                args.iter().skip(1).for_each(|body| self.check_expression(body));
+                self.unbind(scope);
+            },
+            Some(NativeFunctions::Match) if args.len() == 4 || args.len() == 5 => {
+                self.check_expression(&args[0]);
+                // (match input some-name some-branch none-branch)
+                // (match input ok-name ok-branch err-name err-branch)
+                let branches: &[(usize, usize)] = if args.len() == 4 { &[(1, 2)] } else { &[(1, 2), (3, 4)] };
+                for (name, branch) in branches.iter() {
+                    let scope = self.bindings.len();
+                    self.bind(&args[*name], CheckWarnings::UnusedMatchBinding, vec![]);
+                    self.check_expression(&args[*branch]);
+                    self.unbind(scope);
+                }
+                if args.len() == 4 {
+                    self.check_expression(&args[3]);
+                }
+            },
+            Some(NativeFunctions::TupleGet) => {
+                // the key is a field name.
+                args.iter().skip(1).for_each(|tuple| self.check_expression(tuple));
+            },
+            Some(NativeFunctions::TupleCons) => {
+                for pair in args.iter().filter_map(|pair| pair.match_list()) {
+                    pair.iter().skip(1).for_each(|value| self.check_expression(value));
+                }
+            },
+            _ => list.iter().for_each(|item| self.check_expression(item))
+        }
+    }
+
+    fn reference(&mut self, name: &ClarityName) {
+        if let Some(binding) = self.bindings.iter_mut().rev().find(|binding| &binding.name == name) {
+            binding.is_used = true;
+        }
+        self.references.insert(name.clone());
+    }
+
+    fn bind(&mut self, name: &SymbolicExpression, warning: fn(String) -> CheckWarnings, context: Vec<SymbolicExpression>) {
+        let atom = match name.match_atom() {
+            Some(atom) => atom,
+            None => return
+        };
+        let mut expressions = vec![name.clone()];
+        expressions.extend(context);
+        self.bindings.push(Binding {
+            warning: warning(atom.to_string()),
+            name: atom.clone(),
+            expressions,
+            is_used: atom.ends_with(UNUSED_MARKER),
+        });
+    }
+
+    /// Drops the bindings going out of scope, reporting the ones never used.
+    fn unbind(&mut self, scope: usize) {
+        for binding in self.bindings.split_off(scope) {
+            if !binding.is_used {
+                self.warnings.push(CheckWarning::new(binding.warning, binding.expressions));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::clarity::ast;
+    use crate::clarity::types::QualifiedContractIdentifier;
+
+    fn warnings(contract: &str) -> Vec<CheckWarnings> {
+        let contract_identifier = QualifiedContractIdentifier::transient();
+        let contract_ast = ast::build_ast(&contract_identifier, contract, &mut ()).unwrap();
+        UnusedChecker::new().run(&contract_ast.expressions).into_iter()
+            .map(|warning| warning.warning)
+            .collect()
+    }
+
+    #[test]
+    fn test_unused_definitions() {
+        let contract = "(define-constant owner tx-sender)
+                        (define-constant limit u10)
+                        (define-map balances principal uint)
+                        (define-map allowances principal uint)
+                        (define-data-var supply uint u0)
+                        (define-fungible-token token)
+                        (define-non-fungible-token badge uint)
+                        (define-private (add (a uint) (b uint)) (+ a b))
+                        (define-private (double (a uint)) (* a u2))
+                        (define-private (is-owner (who principal)) (is-eq who owner))
+                        (define-public (mint (amount uint))
+                            (begin
+                                (asserts! (is-owner tx-sender) (err u1))
+                                (map-set balances tx-sender (fold add (list amount) u0))
+                                (ft-mint? token amount tx-sender)))
+                        (define-read-only (get-supply) (var-get supply))";
+        assert_eq!(warnings(contract), vec![
+            CheckWarnings::UnusedConstant("limit".to_string()),
+            CheckWarnings::UnusedMap("allowances".to_string()),
+            CheckWarnings::UnusedToken("badge".to_string()),
+            CheckWarnings::UnusedPrivateFunction("double".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_unused_bindings() {
+        let contract = "(define-read-only (f (a uint) (b uint) (c_ uint))
+                            (let ((x (+ a u1)) (y a) (z u2))
+                                (match (some (+ x y)) value (get value {value: u1}) u0)))
+                        (define-read-only (g (r (response uint uint)))
+                            (match r value value error u0))";
+        assert_eq!(warnings(contract), vec![
+            CheckWarnings::UnusedMatchBinding("value".to_string()),
+            CheckWarnings::UnusedLetBinding("z".to_string()),
+            CheckWarnings::UnusedParameter("b".to_string()),
+            CheckWarnings::UnusedMatchBinding("error".to_string()),
+        ]);
+    }
+}
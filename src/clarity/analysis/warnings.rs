@@ -0,0 +1,153 @@
+use crate::clarity::representations::SymbolicExpression;
+use crate::clarity::diagnostic::{Diagnostic, DiagnosableError};
+use std::fmt;
+
+/// Findings of the lint passes: the contract is valid, but likely does not do what
+/// its author intended.
+#[derive(Debug, PartialEq)]
+pub enum CheckWarnings {
+    // unused definitions
+    UnusedPrivateFunction(String),
+    UnusedConstant(String),
+    UnusedMap(String),
+    UnusedDataVar(String),
+    UnusedToken(String),
+
+    // unused bindings
+    UnusedParameter(String),
+    UnusedLetBinding(String),
+    UnusedMatchBinding(String),
+
+    // unchecked results
+    UncheckedResponse,
+    IgnoredResult(String),
+
+    // senders
+    TxSenderAuthorization,
+    CallerSuppliedRecipient(String),
+    CallerSuppliedSender(String),
+
+    // taint analysis
+    UntrustedArgument(String, String),
+
+    // arithmetic
+    UIntUnderflow,
+    DivisionByZero,
+    ArithmeticOverflow(String),
+
+    // dead code
+    ConstantCondition(bool),
+    ConstantMatch,
+    UnreachableCode,
+    RedundantUnwrap(String),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CheckWarning {
+    pub warning: CheckWarnings,
+    /// The expression reported, followed by the expressions enclosing it that a fix
+    /// needs, e.g. the definition of an unused name.
+    pub expressions: Vec<SymbolicExpression>,
+    pub diagnostic: Diagnostic,
+}
+
+impl CheckWarning {
+    pub fn new(warning: CheckWarnings, expressions: Vec<SymbolicExpression>) -> CheckWarning {
+        let mut diagnostic = Diagnostic::warning(&warning);
+        diagnostic.spans = expressions.iter().take(1).map(|e| e.span.clone()).collect();
+        CheckWarning {
+            warning,
+            expressions,
+            diagnostic
+        }
+    }
+}
+
+impl fmt::Display for CheckWarnings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl fmt::Display for CheckWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.warning)?;
+        if let Some(e) = self.expressions.first() {
+            write!(f, "\nNear:\n{:?}", e)?;
+        }
+        Ok(())
+    }
+}
+
+impl DiagnosableError for CheckWarnings {
+
+    fn message(&self) -> String {
+        match &self {
+            CheckWarnings::UnusedPrivateFunction(name) => format!("private function '{}' is never called", name),
+            CheckWarnings::UnusedConstant(name) => format!("constant '{}' is never used", name),
+            CheckWarnings::UnusedMap(name) => format!("map '{}' is never used", name),
+            CheckWarnings::UnusedDataVar(name) => format!("data var '{}' is never used", name),
+            CheckWarnings::UnusedToken(name) => format!("token '{}' is never used", name),
+            CheckWarnings::UnusedParameter(name) => format!("parameter '{}' is never used", name),
+            CheckWarnings::UnusedLetBinding(name) => format!("let binding '{}' is never used", name),
+            CheckWarnings::UnusedMatchBinding(name) => format!("match binding '{}' is never used", name),
+            CheckWarnings::UncheckedResponse => "this response is dropped, an error would go unnoticed".into(),
+            CheckWarnings::IgnoredResult(function) => format!("the result of '{}' is dropped, a failure would go unnoticed", function),
+            CheckWarnings::TxSenderAuthorization => "tx-sender is checked in a function other contracts can call: any contract the user calls can act on their behalf".into(),
+            CheckWarnings::CallerSuppliedRecipient(name) => format!("assets are moved out of the contract to '{}', a principal supplied by the caller", name),
+            CheckWarnings::CallerSuppliedSender(name) => format!("the contract moves assets out of '{}', a principal supplied by the caller", name),
+            CheckWarnings::UntrustedArgument(argument, sink) => format!("'{}' is set by the caller, and reaches {} unchecked", argument, sink),
+            CheckWarnings::UIntUnderflow => "this uint subtraction can go below zero, aborting the transaction".into(),
+            CheckWarnings::DivisionByZero => "the divisor can be zero, aborting the transaction".into(),
+            CheckWarnings::ArithmeticOverflow(function) => format!("'{}' overflows 128 bits with these operands, aborting the transaction", function),
+            CheckWarnings::ConstantCondition(value) => format!("this condition is always {}", value),
+            CheckWarnings::ConstantMatch => "the value matched is always none, the other branch never runs".into(),
+            CheckWarnings::UnreachableCode => "this code is never run, the expression before it always aborts".into(),
+            CheckWarnings::RedundantUnwrap(function) => format!("'{}' always succeeds on this value", function),
+        }
+    }
+
+    fn suggestion(&self) -> Option<String> {
+        match &self {
+            CheckWarnings::UnusedParameter(name) | CheckWarnings::UnusedLetBinding(name) | CheckWarnings::UnusedMatchBinding(name) =>
+                Some(format!("If this is intentional, rename it to '{}_'", name)),
+            CheckWarnings::UncheckedResponse => Some("Check it with (try! ...) or (unwrap! ...)".into()),
+            CheckWarnings::IgnoredResult(_) => Some("Check it with (asserts! ...)".into()),
+            CheckWarnings::TxSenderAuthorization => Some("Check contract-caller instead".into()),
+            CheckWarnings::CallerSuppliedRecipient(_) => Some("Make sure the caller is allowed to withdraw these assets".into()),
+            CheckWarnings::CallerSuppliedSender(_) => Some("Make sure the caller owns these assets".into()),
+            CheckWarnings::UntrustedArgument(argument, _) => Some(format!("Check '{}' with (asserts! ...) first", argument)),
+            CheckWarnings::UIntUnderflow => Some("Check that the operands are in order with (asserts! ...) or (if ...) first".into()),
+            CheckWarnings::DivisionByZero => Some("Check that the divisor is not zero with (asserts! ...) or (if ...) first".into()),
+            _ => None
+        }
+    }
+
+    fn code(&self) -> String {
+        // codes are published, a variant keeps its number once assigned.
+        let number = match self {
+            CheckWarnings::UnusedPrivateFunction(..) => 1,
+            CheckWarnings::UnusedConstant(..) => 2,
+            CheckWarnings::UnusedMap(..) => 3,
+            CheckWarnings::UnusedDataVar(..) => 4,
+            CheckWarnings::UnusedToken(..) => 5,
+            CheckWarnings::UnusedParameter(..) => 6,
+            CheckWarnings::UnusedLetBinding(..) => 7,
+            CheckWarnings::UnusedMatchBinding(..) => 8,
+            CheckWarnings::UncheckedResponse => 9,
+            CheckWarnings::IgnoredResult(..) => 10,
+            CheckWarnings::TxSenderAuthorization => 11,
+            CheckWarnings::CallerSuppliedRecipient(..) => 12,
+            CheckWarnings::UntrustedArgument(..) => 13,
+            CheckWarnings::UIntUnderflow => 14,
+            CheckWarnings::DivisionByZero => 15,
+            CheckWarnings::ArithmeticOverflow(..) => 16,
+            CheckWarnings::ConstantCondition(..) => 17,
+            CheckWarnings::ConstantMatch => 18,
+            CheckWarnings::UnreachableCode => 19,
+            CheckWarnings::RedundantUnwrap(..) => 20,
+            CheckWarnings::CallerSuppliedSender(..) => 21,
+        };
+        format!("CLR-W{:04}", number)
+    }
+}
@@ -0,0 +1,254 @@
+use std::fmt;
+
+use crate::clarity::ast::parser::{lex_with_offsets, LexItem};
+use crate::clarity::representations::Span;
+
+/// Whitespace and comments, kept with the token following them.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Trivia {
+    Whitespace(String),
+    Comment(String, Span),
+}
+
+impl Trivia {
+    pub fn text(&self) -> &str {
+        match self {
+            Trivia::Whitespace(text) | Trivia::Comment(text, _) => text
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Token {
+    pub leading_trivia: Vec<Trivia>,
+    pub text: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct Delimited {
+    pub open: Token,
+    pub children: Vec<CstNode>,
+    /// The closing delimiter, unless the list is left open at the end of the source.
+    /// Trivia before the closing delimiter is kept with it.
+    pub close: Option<Token>,
+}
+
+#[derive(Debug, Clone)]
+pub enum CstNode {
+    Atom(Token, LexItem),
+    List(Delimited),
+    Tuple(Delimited),
+    /// `,` and `:` in tuples.
+    Separator(Token),
+    /// Input the lexer could not read, or a closing delimiter without its opening one.
+    Error(Token),
+}
+
+/// Concrete syntax tree of a contract, which keeps every byte of the source: printing
+/// the tree gives back the source as written.
+#[derive(Debug, Clone)]
+pub struct ConcreteSyntaxTree {
+    pub nodes: Vec<CstNode>,
+    /// Trivia after the last token of the source.
+    pub trailing_trivia: Vec<Trivia>,
+}
+
+impl CstNode {
+    /// The first token of the node, carrying the trivia written before the node.
+    pub fn first_token(&self) -> &Token {
+        match self {
+            CstNode::Atom(token, _) | CstNode::Separator(token) | CstNode::Error(token) => token,
+            CstNode::List(delimited) | CstNode::Tuple(delimited) => &delimited.open,
+        }
+    }
+
+    pub fn children(&self) -> &[CstNode] {
+        match self {
+            CstNode::List(delimited) | CstNode::Tuple(delimited) => &delimited.children,
+            _ => &[]
+        }
+    }
+
+    /// Span of the node, trivia excluded.
+    pub fn span(&self) -> Span {
+        let delimited = match self {
+            CstNode::List(delimited) | CstNode::Tuple(delimited) => delimited,
+            _ => return self.first_token().span.clone()
+        };
+        let end = match (&delimited.close, delimited.children.last()) {
+            (Some(close), _) => close.span.clone(),
+            (None, Some(child)) => child.span(),
+            (None, None) => delimited.open.span.clone(),
+        };
+        Span {
+            start_line: delimited.open.span.start_line,
+            start_column: delimited.open.span.start_column,
+            end_line: end.end_line,
+            end_column: end.end_column,
+        }
+    }
+}
+
+/// Builds the concrete syntax tree of the source. Unlike the parser, it never fails:
+/// unreadable input and unbalanced delimiters are kept in the tree as they are.
+pub fn parse(source: &str) -> ConcreteSyntaxTree {
+    let tokens = lex_with_offsets(source);
+
+    // open lists and tuples, with their opening token and the nodes collected so far.
+    let mut stack: Vec<(bool, Token, Vec<CstNode>)> = vec![];
+    let mut nodes = vec![];
+    let mut trivia = vec![];
+
+    for (index, (item, span, start)) in tokens.iter().enumerate() {
+        let end = tokens.get(index + 1).map(|(_, _, next_start)| *next_start).unwrap_or(source.len());
+        let text = source[*start..end].to_string();
+        let token = match item {
+            LexItem::Whitespace => {
+                trivia.push(Trivia::Whitespace(text));
+                continue;
+            },
+            LexItem::Comment(_) => {
+                trivia.push(Trivia::Comment(text, span.clone()));
+                continue;
+            },
+            _ => Token {
+                leading_trivia: std::mem::take(&mut trivia),
+                text,
+                span: span.clone(),
+            }
+        };
+
+        let node = match item {
+            LexItem::LeftParen | LexItem::LeftCurly => {
+                stack.push((matches!(item, LexItem::LeftCurly), token, vec![]));
+                continue;
+            },
+            LexItem::RightParen | LexItem::RightCurly => {
+                let closes_innermost = match stack.last() {
+                    Some((is_tuple, _, _)) => *is_tuple == matches!(item, LexItem::RightCurly),
+                    None => false
+                };
+                if closes_innermost {
+                    let (is_tuple, open, children) = stack.pop().unwrap();
+                    delimited_node(is_tuple, Delimited { open, children, close: Some(token) })
+                } else {
+                    CstNode::Error(token)
+                }
+            },
+            LexItem::CommaSeparator | LexItem::ColonSeparator => CstNode::Separator(token),
+            LexItem::Placeholder(_) => CstNode::Error(token),
+            _ => CstNode::Atom(token, item.clone()),
+        };
+        push_node(&mut stack, &mut nodes, node);
+    }
+
+    // lists left open end with the source.
+    while let Some((is_tuple, open, children)) = stack.pop() {
+        let node = delimited_node(is_tuple, Delimited { open, children, close: None });
+        push_node(&mut stack, &mut nodes, node);
+    }
+
+    ConcreteSyntaxTree {
+        nodes,
+        trailing_trivia: trivia,
+    }
+}
+
+fn delimited_node(is_tuple: bool, delimited: Delimited) -> CstNode {
+    if is_tuple {
+        CstNode::Tuple(delimited)
+    } else {
+        CstNode::List(delimited)
+    }
+}
+
+fn push_node(stack: &mut [(bool, Token, Vec<CstNode>)], nodes: &mut Vec<CstNode>, node: CstNode) {
+    match stack.last_mut() {
+        Some((_, _, children)) => children.push(node),
+        None => nodes.push(node),
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for trivia in self.leading_trivia.iter() {
+            write!(f, "{}", trivia.text())?;
+        }
+        write!(f, "{}", self.text)
+    }
+}
+
+impl fmt::Display for CstNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CstNode::Atom(token, _) | CstNode::Separator(token) | CstNode::Error(token) => write!(f, "{}", token),
+            CstNode::List(delimited) | CstNode::Tuple(delimited) => {
+                write!(f, "{}", delimited.open)?;
+                for child in delimited.children.iter() {
+                    write!(f, "{}", child)?;
+                }
+                match delimited.close {
+                    Some(ref close) => write!(f, "{}", close),
+                    None => Ok(())
+                }
+            },
+        }
+    }
+}
+
+impl fmt::Display for ConcreteSyntaxTree {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for node in self.nodes.iter() {
+            write!(f, "{}", node)?;
+        }
+        for trivia in self.trailing_trivia.iter() {
+            write!(f, "{}", trivia.text())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn test_printing_gives_back_the_source() {
+        let sources = [
+            ";; counter\n(define-data-var counter uint u0) ;; the count\n\n\t(define-public (add)\n  (ok {a: 1, b: u2}))   \n",
+            "(define-constant owner 'SP000000000000000000002Q6VF78)\r\n(print \"text\" 0x0102 .contract <trait>)",
+            "(define-public (broken) (ok u1 ",
+            ")) (ok 123abc é) }",
+            "",
+        ];
+        for source in sources.iter() {
+            assert_eq!(parse(source).to_string(), *source);
+        }
+    }
+
+    #[test]
+    fn test_trivia_is_attached_to_the_following_token() {
+        let tree = parse(";; doc\n(define-data-var counter uint u0) ;; the count\n");
+        assert_eq!(tree.nodes.len(), 1);
+        assert_eq!(tree.nodes[0].first_token().leading_trivia, vec![
+            Trivia::Comment(";; doc".to_string(), Span { start_line: 1, start_column: 1, end_line: 1, end_column: 6 }),
+            Trivia::Whitespace("\n".to_string()),
+        ]);
+        assert_eq!(tree.nodes[0].children().len(), 4);
+        assert_eq!(tree.nodes[0].span(), Span { start_line: 2, start_column: 1, end_line: 2, end_column: 33 });
+        assert_eq!(tree.trailing_trivia.len(), 3);
+
+        let tree = parse("(ok (some u1");
+        assert_eq!(tree.nodes.len(), 1);
+        assert_eq!(tree.nodes[0].children()[1].children().len(), 2);
+        assert_eq!(tree.nodes[0].span(), Span { start_line: 1, start_column: 1, end_line: 1, end_column: 12 });
+
+        let tree = parse("(a}");
+        match tree.nodes[0] {
+            CstNode::List(ref list) => assert!(list.close.is_none()),
+            _ => panic!("expected a list"),
+        }
+        assert!(matches!(tree.nodes[0].children()[1], CstNode::Error(_)));
+    }
+}
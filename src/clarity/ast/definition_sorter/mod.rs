@@ -1,7 +1,7 @@
 use std::collections::{HashSet, HashMap};
 use std::iter::FromIterator;
-use crate::clarity::representations::{SymbolicExpression, PreSymbolicExpression, ClarityName};
-use crate::clarity::representations::PreSymbolicExpressionType::{AtomValue, Atom, List, TraitReference, Tuple, FieldIdentifier, SugaredContractIdentifier, SugaredFieldIdentifier};
+use crate::clarity::representations::{SymbolicExpression, PreSymbolicExpression, ClarityName, Span};
+use crate::clarity::representations::PreSymbolicExpressionType::{AtomValue, Atom, List, TraitReference, Tuple, FieldIdentifier, SugaredContractIdentifier, SugaredFieldIdentifier, Placeholder};
 use crate::clarity::functions::NativeFunctions;
 use crate::clarity::functions::DefineFunctions;
 use crate::clarity::ast::types::{ContractAST, BuildASTPass, PreExpressionsDrain};
@@ -12,7 +12,8 @@ use crate::clarity::costs::{CostTracker, cost_functions};
 
 pub struct DefinitionSorter {
     graph: Graph,
-    top_level_expressions_map: HashMap<ClarityName, TopLevelExpressionIndex>   
+    top_level_expressions_map: HashMap<ClarityName, TopLevelExpressionIndex>,
+    references: Vec<(ClarityName, Span)>,
 }
 
 impl <'a> DefinitionSorter {
@@ -20,7 +21,8 @@ impl <'a> DefinitionSorter {
     fn new() -> Self {
         Self { 
             top_level_expressions_map: HashMap::new(),
-            graph: Graph::new()
+            graph: Graph::new(),
+            references: Vec::new(),
         }
     }
 
@@ -30,23 +32,18 @@ impl <'a> DefinitionSorter {
         Ok(())
     }
 
+    /// Lists the atoms referring to top-level definitions, as found while probing for
+    /// dependencies: the name of the definition, along with the span of the atom.
+    /// Pre-expressions are expected to have been identified.
+    pub fn find_top_level_references(pre_expressions: &[PreSymbolicExpression]) -> ParseResult<Vec<(ClarityName, Span)>> {
+        let mut pass = DefinitionSorter::new();
+        pass.probe_top_level_expressions(pre_expressions)?;
+        Ok(pass.references)
+    }
+
     pub fn run<T: CostTracker>(&mut self, contract_ast: &mut ContractAST, accounting: &mut T) -> ParseResult<()> {
         let exprs = contract_ast.pre_expressions[..].to_vec();
-        for (expr_index, expr) in exprs.iter().enumerate() {
-            self.graph.add_node(expr_index);
-
-            match self.find_expression_definition(expr) {
-                Some((definition_name, atom_index, _)) => {
-                    let tle = TopLevelExpressionIndex { expr_index, atom_index };
-                    self.top_level_expressions_map.insert(definition_name, tle);
-                },
-                None => {}
-            }
-        }
-
-        for (expr_index, expr) in exprs.iter().enumerate() {
-            self.probe_for_dependencies(&expr, expr_index)?;
-        }
+        self.probe_top_level_expressions(&exprs)?;
 
         runtime_cost!(cost_functions::AST_CYCLE_DETECTION, accounting, self.graph.edges_count()?)?;
 
@@ -71,12 +68,32 @@ impl <'a> DefinitionSorter {
         Ok(())
     }
 
+    fn probe_top_level_expressions(&mut self, exprs: &[PreSymbolicExpression]) -> ParseResult<()> {
+        for (expr_index, expr) in exprs.iter().enumerate() {
+            self.graph.add_node(expr_index);
+
+            match self.find_expression_definition(expr) {
+                Some((definition_name, atom_index, _)) => {
+                    let tle = TopLevelExpressionIndex { expr_index, atom_index };
+                    self.top_level_expressions_map.insert(definition_name, tle);
+                },
+                None => {}
+            }
+        }
+
+        for (expr_index, expr) in exprs.iter().enumerate() {
+            self.probe_for_dependencies(&expr, expr_index)?;
+        }
+        Ok(())
+    }
+
     fn probe_for_dependencies(&mut self, expr: &PreSymbolicExpression, tle_index: usize) -> ParseResult<()> {
         match expr.pre_expr {
             Atom(ref name) => {
                 if let Some(dep) = self.top_level_expressions_map.get(name) {
                     if dep.atom_index != expr.id {
                         self.graph.add_directed_edge(tle_index, dep.expr_index);
+                        self.references.push((name.clone(), expr.span.clone()));
                     }
                 }
                 Ok(())
@@ -85,6 +102,7 @@ impl <'a> DefinitionSorter {
                 if let Some(dep) = self.top_level_expressions_map.get(name) {
                     if dep.atom_index != expr.id {
                         self.graph.add_directed_edge(tle_index, dep.expr_index);
+                        self.references.push((name.clone(), expr.span.clone()));
                     }
                 }
                 Ok(())
@@ -216,7 +234,7 @@ impl <'a> DefinitionSorter {
                 self.probe_for_dependencies_in_tuple_list(exprs, tle_index)?;
                 Ok(())
             },
-            AtomValue(_) | FieldIdentifier(_) | SugaredContractIdentifier(_) | SugaredFieldIdentifier(_, _) => Ok(()),
+            AtomValue(_) | FieldIdentifier(_) | SugaredContractIdentifier(_) | SugaredFieldIdentifier(_, _) | Placeholder(_) => Ok(()),
         }
     }
 
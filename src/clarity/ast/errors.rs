@@ -1,4 +1,4 @@
-use crate::clarity::representations::{SymbolicExpression, PreSymbolicExpression};
+use crate::clarity::representations::{SymbolicExpression, PreSymbolicExpression, Span};
 use crate::clarity::diagnostic::{Diagnostic, DiagnosableError};
 use crate::clarity::types::{TypeSignature, TupleTypeSignature};
 use crate::clarity::MAX_CALL_STACK_DEPTH;
@@ -72,6 +72,10 @@ impl ParseError {
         self.pre_expressions.replace(vec![expr.clone()]);
     }
 
+    pub fn set_span(&mut self, span: Span) {
+        self.diagnostic.spans = vec![span];
+    }
+
     pub fn set_pre_expressions(&mut self, exprs: Vec<PreSymbolicExpression>) {
         self.diagnostic.spans = exprs.iter().map(|e| e.span.clone()).collect();
         self.pre_expressions.replace(exprs.clone().to_vec());
@@ -163,4 +167,45 @@ impl DiagnosableError for ParseErrors {
             _ => None
         }
     }
+
+    fn code(&self) -> String {
+        // codes are published, a variant keeps its number once assigned.
+        let number = match self {
+            ParseErrors::CostOverflow => 1,
+            ParseErrors::CostBalanceExceeded(..) => 2,
+            ParseErrors::MemoryBalanceExceeded(..) => 3,
+            ParseErrors::TooManyExpressions => 4,
+            ParseErrors::ExpressionStackDepthTooDeep => 5,
+            ParseErrors::FailedCapturingInput => 6,
+            ParseErrors::SeparatorExpected(..) => 7,
+            ParseErrors::SeparatorExpectedAfterColon(..) => 8,
+            ParseErrors::ProgramTooLarge => 9,
+            ParseErrors::IllegalVariableName(..) => 10,
+            ParseErrors::IllegalContractName(..) => 11,
+            ParseErrors::UnknownQuotedValue(..) => 12,
+            ParseErrors::FailedParsingIntValue(..) => 13,
+            ParseErrors::FailedParsingBuffer(..) => 14,
+            ParseErrors::FailedParsingHexValue(..) => 15,
+            ParseErrors::FailedParsingPrincipal(..) => 16,
+            ParseErrors::FailedParsingField(..) => 17,
+            ParseErrors::FailedParsingRemainder(..) => 18,
+            ParseErrors::ClosingParenthesisUnexpected => 19,
+            ParseErrors::ClosingParenthesisExpected => 20,
+            ParseErrors::ClosingTupleLiteralUnexpected => 21,
+            ParseErrors::ClosingTupleLiteralExpected => 22,
+            ParseErrors::CircularReference(..) => 23,
+            ParseErrors::TupleColonExpected(..) => 24,
+            ParseErrors::TupleCommaExpected(..) => 25,
+            ParseErrors::TupleItemExpected(..) => 26,
+            ParseErrors::NameAlreadyUsed(..) => 27,
+            ParseErrors::TraitReferenceNotAllowed => 28,
+            ParseErrors::ImportTraitBadSignature => 29,
+            ParseErrors::DefineTraitBadSignature => 30,
+            ParseErrors::ImplTraitBadSignature => 31,
+            ParseErrors::TraitReferenceUnknown(..) => 32,
+            ParseErrors::CommaSeparatorUnexpected => 33,
+            ParseErrors::ColonSeparatorUnexpected => 34,
+        };
+        format!("CLR-P{:04}", number)
+    }
 }
@@ -1,4 +1,5 @@
 pub mod parser;
+pub mod cst;
 pub mod expression_identifier;
 pub mod definition_sorter;
 pub mod traits_resolver;
@@ -16,7 +17,7 @@ use crate::clarity::types::QualifiedContractIdentifier;
 
 pub use self::types::ContractAST;
 use self::types::BuildASTPass;
-use self::errors::ParseResult;
+use self::errors::{ParseResult, ParseError};
 use self::expression_identifier::ExpressionIdentifier;
 use self::sugar_expander::SugarExpander;
 use self::definition_sorter::DefinitionSorter;
@@ -35,3 +36,31 @@ pub fn build_ast<T: CostTracker>(contract_identifier: &QualifiedContractIdentifi
     ExpressionIdentifier::run_expression_pass(&mut contract_ast)?;
     Ok(contract_ast)
 }
+
+/// Builds the AST of a contract being edited: parse errors do not abort the build, and
+/// the remaining passes run over whatever could be parsed. The pre-expressions are kept
+/// in the returned AST, along with every error met.
+pub fn build_ast_with_recovery<T: CostTracker>(contract_identifier: &QualifiedContractIdentifier, source_code: &str, cost_track: &mut T) -> (ContractAST, Vec<ParseError>) {
+    let (pre_expressions, mut errors) = parser::parse_with_recovery(source_code);
+    let mut contract_ast = ContractAST::new(contract_identifier.clone(), pre_expressions);
+    let result = runtime_cost!(cost_functions::AST_PARSE, cost_track, source_code.len() as u64)
+        .map_err(ParseError::from)
+        .and_then(|_| StackDepthChecker::run_pass(&mut contract_ast))
+        .and_then(|_| ExpressionIdentifier::run_pre_expression_pass(&mut contract_ast))
+        .and_then(|_| DefinitionSorter::run_pass(&mut contract_ast, cost_track))
+        .and_then(|_| TraitsResolver::run_pass(&mut contract_ast));
+    if let Err(error) = result {
+        errors.push(error);
+        return (contract_ast, errors);
+    }
+
+    // the sugar expander drains the pre-expressions.
+    let pre_expressions = contract_ast.pre_expressions.clone();
+    let result = SugarExpander::run_pass(&mut contract_ast)
+        .and_then(|_| ExpressionIdentifier::run_expression_pass(&mut contract_ast));
+    if let Err(error) = result {
+        errors.push(error);
+    }
+    contract_ast.pre_expressions = pre_expressions;
+    (contract_ast, errors)
+}
@@ -5,12 +5,13 @@ use regex::{Regex, Captures};
 use crate::clarity::util::c32::c32_address_decode;
 use crate::clarity::ast::errors::{ParseResult, ParseErrors, ParseError};
 use crate::clarity::errors::{InterpreterResult as Result};
-use crate::clarity::representations::{PreSymbolicExpression, PreSymbolicExpressionType, ContractName, ClarityName, MAX_STRING_LEN};
+use crate::clarity::representations::{PreSymbolicExpression, PreSymbolicExpressionType, ContractName, ClarityName, Span, MAX_STRING_LEN};
 use crate::clarity::types::{Value, PrincipalData, TraitIdentifier, QualifiedContractIdentifier};
 
 pub const CONTRACT_MIN_NAME_LENGTH : usize = 5;
 pub const CONTRACT_MAX_NAME_LENGTH : usize = 40;
 
+#[derive(Debug, Clone)]
 pub enum LexItem {
     LeftParen,
     RightParen,
@@ -24,12 +25,14 @@ pub enum LexItem {
     Variable(String),
     CommaSeparator,
     ColonSeparator,
-    Whitespace
+    Whitespace,
+    Comment(String),
+    Placeholder(String),
 }
 
 #[derive(Debug)]
 enum TokenType {
-    Whitespace, Comma, Colon,
+    Whitespace, Comment, Comma, Colon,
     LParens, RParens,
     LCurly, RCurly,
     StringLiteral, HexStringLiteral,
@@ -57,6 +60,34 @@ enum ParseContext {
     CollectTuple,
 }
 
+/// Decides what happens to the errors met while lexing and parsing: they either abort
+/// right away, or get recorded while the parser recovers and carries on.
+struct ErrorReporter {
+    recover: bool,
+    errors: Vec<ParseError>,
+}
+
+impl ErrorReporter {
+    fn fail_fast() -> ErrorReporter {
+        ErrorReporter { recover: false, errors: Vec::new() }
+    }
+
+    fn recovering() -> ErrorReporter {
+        ErrorReporter { recover: true, errors: Vec::new() }
+    }
+
+    fn report_at(&mut self, err: ParseErrors, span: Span) -> ParseResult<()> {
+        let mut error = ParseError::new(err);
+        error.set_span(span);
+        if self.recover {
+            self.errors.push(error);
+            Ok(())
+        } else {
+            Err(error)
+        }
+    }
+}
+
 impl LexMatcher {
     fn new(regex_str: &str, handles: TokenType) -> LexMatcher {
         LexMatcher {
@@ -91,13 +122,38 @@ lazy_static! {
 }
 
 pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
+    lex_reporting(input, &mut ErrorReporter::fail_fast())
+}
+
+fn lex_reporting(input: &str, reporter: &mut ErrorReporter) -> ParseResult<Vec<(LexItem, u32, u32)>> {
+    let tokens = lex_spanned(input, reporter)?;
+    Ok(tokens.into_iter()
+        .map(|(token, span, _)| (token, span.start_line, span.start_column))
+        .collect())
+}
+
+/// Lexes the input without stopping at the first error, along with the span of each
+/// token as written in the source, e.g. `u10` rather than its value `10`.
+pub fn lex_with_recovery(input: &str) -> Vec<(LexItem, Span)> {
+    lex_with_offsets(input).into_iter()
+        .map(|(token, span, _)| (token, span))
+        .collect()
+}
+
+/// Lexes the input like `lex_with_recovery`, along with the byte offset each token
+/// starts at. The tokens cover the whole input, whitespace and comments included.
+pub fn lex_with_offsets(input: &str) -> Vec<(LexItem, Span, usize)> {
+    lex_spanned(input, &mut ErrorReporter::recovering()).unwrap_or_default()
+}
+
+fn lex_spanned(input: &str, reporter: &mut ErrorReporter) -> ParseResult<Vec<(LexItem, Span, usize)>> {
     // Aaron: I'd like these to be static, but that'd require using
     //    lazy_static (or just hand implementing that), and I'm not convinced
     //    it's worth either (1) an extern macro, or (2) the complexity of hand implementing.
 
     let lex_matchers: &[LexMatcher] = &[
         LexMatcher::new(r##""(?P<value>((\\")|([[ -~]&&[^"]]))*)""##, TokenType::StringLiteral),
-        LexMatcher::new(";;[ -~]*", TokenType::Whitespace), // ;; comments.
+        LexMatcher::new(";;[ -~]*", TokenType::Comment),
         LexMatcher::new("[\n]+", TokenType::Whitespace),
         LexMatcher::new("[ \t]+", TokenType::Whitespace),
         LexMatcher::new("[,]", TokenType::Comma),
@@ -129,192 +185,244 @@ pub fn lex(input: &str) -> ParseResult<Vec<(LexItem, u32, u32)>> {
     let mut result = Vec::new();
     let mut munch_index = 0;
     let mut column_pos: u32 = 1;
-    let mut did_match = true;
-    while did_match && munch_index < input.len() {
-        if let Some(next_line_ix) = next_line_break {
-            if munch_index > next_line_ix {
-                next_line_break = line_indices.pop();
-                column_pos = 1;
-                current_line = current_line.checked_add(1)
-                    .ok_or(ParseError::new(ParseErrors::ProgramTooLarge))?;
+    while munch_index < input.len() {
+        // a single whitespace token can span several line breaks.
+        while let Some(next_line_ix) = next_line_break {
+            if munch_index <= next_line_ix {
+                break;
             }
+            next_line_break = line_indices.pop();
+            column_pos = 1;
+            current_line = current_line.checked_add(1)
+                .ok_or(ParseError::new(ParseErrors::ProgramTooLarge))?;
         }
 
-        did_match = false;
         let current_slice = &input[munch_index..];
-        for matcher in lex_matchers.iter() {
-            if let Some(captures) = matcher.matcher.captures(current_slice) {
-                let whole_match = captures.get(0).unwrap();
-                assert_eq!(whole_match.start(), 0);
-                munch_index += whole_match.end();
-
-                match context {
-                    LexContext::ExpectNothing => Ok(()),
-                    LexContext::ExpectClosing => {
-                        // expect the next lexed item to be something that typically
-                        // "closes" an atom -- i.e., whitespace or a right-parens.
-                        // this prevents an atom like 1234abc from getting split into "1234" and "abc"
-                        match matcher.handler {
-                            TokenType::RParens => Ok(()),
-                            TokenType::RCurly => Ok(()),
-                            TokenType::Whitespace => Ok(()),
-                            TokenType::Comma => Ok(()),
-                            TokenType::Colon => Ok(()),
-                            _ => Err(ParseError::new(ParseErrors::SeparatorExpected(current_slice[..whole_match.end()].to_string())))
-                        }
-                    },
-                    LexContext::ExpectClosingColon => {
-                        // handle the expected whitespace after a `:`
-                        match matcher.handler {
-                            TokenType::RParens => Ok(()),
-                            TokenType::RCurly => Ok(()),
-                            TokenType::Whitespace => Ok(()),
-                            TokenType::Comma => Ok(()),
-                            TokenType::Colon => Ok(()),
-                            _ => Err(ParseError::new(ParseErrors::SeparatorExpectedAfterColon(
-                                current_slice[..whole_match.end()].to_string())))
-                        }
-                    }
-
-                }?;
+        let matched = lex_matchers.iter()
+            .find_map(|matcher| matcher.matcher.captures(current_slice).map(|captures| (matcher, captures)));
 
-                // default to expect a closing
+        let (matcher, captures) = match matched {
+            Some(matched) => matched,
+            None => {
+                if !reporter.recover {
+                    return Err(ParseError::new(ParseErrors::FailedParsingRemainder(current_slice.to_string())))
+                }
+                // skip the unknown input up to the next separator, and carry on from there.
+                let skipped_len = current_slice.char_indices()
+                    .skip(1)
+                    .find(|(_, c)| c.is_whitespace() || "(){},:".contains(*c))
+                    .map(|(ix, _)| ix)
+                    .unwrap_or(current_slice.len());
+                let skipped = &current_slice[..skipped_len];
+                let span = token_span(current_line, column_pos, skipped);
+                reporter.report_at(ParseErrors::FailedParsingRemainder(skipped.to_string()), span.clone())?;
+                result.push((LexItem::Placeholder(skipped.to_string()), span, munch_index));
+                munch_index += skipped_len;
+                column_pos += skipped.encode_utf16().count() as u32;
                 context = LexContext::ExpectClosing;
+                continue;
+            }
+        };
 
-                let token = match matcher.handler {
-                    TokenType::LParens => {
-                        context = LexContext::ExpectNothing;
-                        Ok(LexItem::LeftParen)
-                    },
-                    TokenType::RParens => {
-                        Ok(LexItem::RightParen)
-                    },
-                    TokenType::Whitespace => {
-                        context = LexContext::ExpectNothing;
-                        Ok(LexItem::Whitespace)
-                    },
-                    TokenType::Comma => {
-                        context = LexContext::ExpectNothing;
-                        Ok(LexItem::CommaSeparator)
-                    },
-                    TokenType::Colon => {
-                        // colon should not be followed directly by an item,
-                        //  e.g., {a:b} should not be legal
-                        context = LexContext::ExpectClosingColon;
-                        Ok(LexItem::ColonSeparator)
-                    },
-                    TokenType::LCurly => {
-                        context = LexContext::ExpectNothing;
-                        Ok(LexItem::LeftCurly)
-                    },
-                    TokenType::RCurly => {
-                        Ok(LexItem::RightCurly)
-                    },
-                    TokenType::Variable => {
-                        let value = get_value_or_err(current_slice, captures)?;
-                        if value.contains("#") {
-                            Err(ParseError::new(ParseErrors::IllegalVariableName(value)))
-                        } else {
-                            Ok(LexItem::Variable(value))
-                        }
-                    },
-                    TokenType::UIntLiteral => {
-                        let str_value = get_value_or_err(current_slice, captures)?;
-                        let value = match u128::from_str_radix(&str_value, 10) {
-                            Ok(parsed) => Ok(Value::UInt(parsed)),
-                            Err(_e) => Err(ParseError::new(ParseErrors::FailedParsingIntValue(str_value.clone())))
-                        }?;
-                        Ok(LexItem::LiteralValue(str_value.len(), value))
-                    },
-                    TokenType::IntLiteral => {
-                        let str_value = get_value_or_err(current_slice, captures)?;
-                        let value = match i128::from_str_radix(&str_value, 10) {
-                            Ok(parsed) => Ok(Value::Int(parsed)),
-                            Err(_e) => Err(ParseError::new(ParseErrors::FailedParsingIntValue(str_value.clone())))
-                        }?;
-                        Ok(LexItem::LiteralValue(str_value.len(), value))
-                    },
-                    TokenType::FullyQualifiedContractIdentifierLiteral => {
-                        let str_value = get_value_or_err(current_slice, captures)?;
-                        let value = match PrincipalData::parse_qualified_contract_principal(&str_value) {
-                            Ok(parsed) => Ok(Value::Principal(parsed)),
-                            Err(_e) => Err(ParseError::new(ParseErrors::FailedParsingPrincipal(str_value.clone())))
-                        }?;
-                        Ok(LexItem::LiteralValue(str_value.len(), value))
-                    },
-                    TokenType::SugaredContractIdentifierLiteral => {
-                        let str_value = get_value_or_err(current_slice, captures)?;
-                        let value = match str_value[1..].to_string().try_into() {
-                            Ok(parsed) => Ok(parsed),
-                            Err(_e) => Err(ParseError::new(ParseErrors::FailedParsingPrincipal(str_value.clone())))
-                        }?;
-                        Ok(LexItem::SugaredContractIdentifier(str_value.len(), value))
-                    },
-                    TokenType::FullyQualifiedFieldIdentifierLiteral => {
-                        let str_value = get_value_or_err(current_slice, captures)?;
-                        let value = match TraitIdentifier::parse_fully_qualified(&str_value) {
-                            Ok(parsed) => Ok(parsed),
-                            Err(_e) => Err(ParseError::new(ParseErrors::FailedParsingField(str_value.clone())))
-                        }?;
-                        Ok(LexItem::FieldIdentifier(str_value.len(), value))
-                    },
-                    TokenType::SugaredFieldIdentifierLiteral => {
-                        let str_value = get_value_or_err(current_slice, captures)?;
-                        let (contract_name, field_name) = match TraitIdentifier::parse_sugared_syntax(&str_value) {
-                            Ok((contract_name, field_name)) => Ok((contract_name, field_name)),
-                            Err(_e) => Err(ParseError::new(ParseErrors::FailedParsingField(str_value.clone())))
-                        }?;
-                        Ok(LexItem::SugaredFieldIdentifier(str_value.len(), contract_name, field_name))
-                    },
-                    TokenType::PrincipalLiteral => {
-                        let str_value = get_value_or_err(current_slice, captures)?;
-                        let value = match PrincipalData::parse_standard_principal(&str_value) {
-                            Ok(parsed) => Ok(Value::Principal(PrincipalData::Standard(parsed))),
-                            Err(_e) => Err(ParseError::new(ParseErrors::FailedParsingPrincipal(str_value.clone())))
-                        }?;
-                        Ok(LexItem::LiteralValue(str_value.len(), value))
-                    },
-                    TokenType::TraitReferenceLiteral => {
-                        let str_value = get_value_or_err(current_slice, captures)?;
-                        let data = str_value.clone().try_into()
-                            .map_err(|_| { ParseError::new(ParseErrors::IllegalVariableName(str_value.to_string())) })?;
-                        Ok(LexItem::TraitReference(str_value.len(), data))
-                    },
-                    TokenType::HexStringLiteral => {
-                        let str_value = get_value_or_err(current_slice, captures)?;
-                        let byte_vec = hex_bytes(&str_value)
-                            .map_err(|x| { ParseError::new(ParseErrors::FailedParsingHexValue(str_value.clone(), x.to_string())) })?;
-                        let value = match Value::buff_from(byte_vec) {
-                            Ok(parsed) => Ok(parsed),
-                            Err(_e) => Err(ParseError::new(ParseErrors::FailedParsingBuffer(str_value.clone())))
-                        }?;
-                        Ok(LexItem::LiteralValue(str_value.len(), value))
-                    },
-                    TokenType::StringLiteral => {
-                        let str_value = get_value_or_err(current_slice, captures)?;
-                        let quote_unescaped = str_value.replace("\\\"","\"");
-                        let slash_unescaped = quote_unescaped.replace("\\\\","\\");
-                        let byte_vec = slash_unescaped.as_bytes().to_vec();
-                        let value = match Value::buff_from(byte_vec) {
-                            Ok(parsed) => Ok(parsed),
-                            Err(_e) => Err(ParseError::new(ParseErrors::FailedParsingBuffer(str_value.clone())))
-                        }?;
-                        Ok(LexItem::LiteralValue(str_value.len(), value))
-                    },
-                }?;
+        let whole_match = captures.get(0).unwrap();
+        assert_eq!(whole_match.start(), 0);
+        let token_len = whole_match.end();
+        let token_text = &current_slice[..token_len];
+        let span = token_span(current_line, column_pos, token_text);
 
-                result.push((token, current_line, column_pos));
-                column_pos += whole_match.end() as u32;
-                did_match = true;
-                break;
+        let context_check = match context {
+            LexContext::ExpectNothing => Ok(()),
+            LexContext::ExpectClosing => {
+                // expect the next lexed item to be something that typically
+                // "closes" an atom -- i.e., whitespace or a right-parens.
+                // this prevents an atom like 1234abc from getting split into "1234" and "abc"
+                match matcher.handler {
+                    TokenType::RParens => Ok(()),
+                    TokenType::RCurly => Ok(()),
+                    TokenType::Whitespace => Ok(()),
+                    TokenType::Comment => Ok(()),
+                    TokenType::Comma => Ok(()),
+                    TokenType::Colon => Ok(()),
+                    _ => Err(ParseError::new(ParseErrors::SeparatorExpected(token_text.to_string())))
+                }
+            },
+            LexContext::ExpectClosingColon => {
+                // handle the expected whitespace after a `:`
+                match matcher.handler {
+                    TokenType::RParens => Ok(()),
+                    TokenType::RCurly => Ok(()),
+                    TokenType::Whitespace => Ok(()),
+                    TokenType::Comment => Ok(()),
+                    TokenType::Comma => Ok(()),
+                    TokenType::Colon => Ok(()),
+                    _ => Err(ParseError::new(ParseErrors::SeparatorExpectedAfterColon(
+                        token_text.to_string())))
+                }
             }
+
+        };
+
+        if let Err(error) = context_check {
+            reporter.report_at(error.err, span.clone())?;
         }
+
+        // default to expect a closing
+        context = LexContext::ExpectClosing;
+
+        let token = match lex_token(&matcher.handler, current_slice, captures, &mut context) {
+            Ok(token) => token,
+            Err(error) => {
+                reporter.report_at(error.err, span.clone())?;
+                LexItem::Placeholder(token_text.to_string())
+            }
+        };
+
+        result.push((token, span, munch_index));
+        munch_index += token_len;
+        column_pos += token_len as u32;
     }
 
-    if munch_index == input.len() {
-        Ok(result)
-    } else {
-        Err(ParseError::new(ParseErrors::FailedParsingRemainder(input[munch_index..].to_string())))
+    Ok(result)
+}
+
+/// Builds the item for a token matched by the lexer, and updates the context expected
+/// for the following token.
+fn lex_token(handler: &TokenType, current_slice: &str, captures: Captures, context: &mut LexContext) -> ParseResult<LexItem> {
+    match handler {
+        TokenType::LParens => {
+            *context = LexContext::ExpectNothing;
+            Ok(LexItem::LeftParen)
+        },
+        TokenType::RParens => {
+            Ok(LexItem::RightParen)
+        },
+        TokenType::Whitespace => {
+            *context = LexContext::ExpectNothing;
+            Ok(LexItem::Whitespace)
+        },
+        TokenType::Comment => {
+            *context = LexContext::ExpectNothing;
+            Ok(LexItem::Comment(current_slice[..captures.get(0).unwrap().end()].to_string()))
+        },
+        TokenType::Comma => {
+            *context = LexContext::ExpectNothing;
+            Ok(LexItem::CommaSeparator)
+        },
+        TokenType::Colon => {
+            // colon should not be followed directly by an item,
+            //  e.g., {a:b} should not be legal
+            *context = LexContext::ExpectClosingColon;
+            Ok(LexItem::ColonSeparator)
+        },
+        TokenType::LCurly => {
+            *context = LexContext::ExpectNothing;
+            Ok(LexItem::LeftCurly)
+        },
+        TokenType::RCurly => {
+            Ok(LexItem::RightCurly)
+        },
+        TokenType::Variable => {
+            let value = get_value_or_err(current_slice, captures)?;
+            if value.contains("#") {
+                Err(ParseError::new(ParseErrors::IllegalVariableName(value)))
+            } else {
+                Ok(LexItem::Variable(value))
+            }
+        },
+        TokenType::UIntLiteral => {
+            let str_value = get_value_or_err(current_slice, captures)?;
+            let value = match u128::from_str_radix(&str_value, 10) {
+                Ok(parsed) => Ok(Value::UInt(parsed)),
+                Err(_e) => Err(ParseError::new(ParseErrors::FailedParsingIntValue(str_value.clone())))
+            }?;
+            Ok(LexItem::LiteralValue(str_value.len() + 1, value))
+        },
+        TokenType::IntLiteral => {
+            let str_value = get_value_or_err(current_slice, captures)?;
+            let value = match i128::from_str_radix(&str_value, 10) {
+                Ok(parsed) => Ok(Value::Int(parsed)),
+                Err(_e) => Err(ParseError::new(ParseErrors::FailedParsingIntValue(str_value.clone())))
+            }?;
+            Ok(LexItem::LiteralValue(str_value.len(), value))
+        },
+        TokenType::FullyQualifiedContractIdentifierLiteral => {
+            let str_value = get_value_or_err(current_slice, captures)?;
+            let value = match PrincipalData::parse_qualified_contract_principal(&str_value) {
+                Ok(parsed) => Ok(Value::Principal(parsed)),
+                Err(_e) => Err(ParseError::new(ParseErrors::FailedParsingPrincipal(str_value.clone())))
+            }?;
+            Ok(LexItem::LiteralValue(str_value.len(), value))
+        },
+        TokenType::SugaredContractIdentifierLiteral => {
+            let str_value = get_value_or_err(current_slice, captures)?;
+            let value = match str_value[1..].to_string().try_into() {
+                Ok(parsed) => Ok(parsed),
+                Err(_e) => Err(ParseError::new(ParseErrors::FailedParsingPrincipal(str_value.clone())))
+            }?;
+            Ok(LexItem::SugaredContractIdentifier(str_value.len(), value))
+        },
+        TokenType::FullyQualifiedFieldIdentifierLiteral => {
+            let str_value = get_value_or_err(current_slice, captures)?;
+            let value = match TraitIdentifier::parse_fully_qualified(&str_value) {
+                Ok(parsed) => Ok(parsed),
+                Err(_e) => Err(ParseError::new(ParseErrors::FailedParsingField(str_value.clone())))
+            }?;
+            Ok(LexItem::FieldIdentifier(str_value.len(), value))
+        },
+        TokenType::SugaredFieldIdentifierLiteral => {
+            let str_value = get_value_or_err(current_slice, captures)?;
+            let (contract_name, field_name) = match TraitIdentifier::parse_sugared_syntax(&str_value) {
+                Ok((contract_name, field_name)) => Ok((contract_name, field_name)),
+                Err(_e) => Err(ParseError::new(ParseErrors::FailedParsingField(str_value.clone())))
+            }?;
+            Ok(LexItem::SugaredFieldIdentifier(str_value.len(), contract_name, field_name))
+        },
+        TokenType::PrincipalLiteral => {
+            let str_value = get_value_or_err(current_slice, captures)?;
+            let value = match PrincipalData::parse_standard_principal(&str_value) {
+                Ok(parsed) => Ok(Value::Principal(PrincipalData::Standard(parsed))),
+                Err(_e) => Err(ParseError::new(ParseErrors::FailedParsingPrincipal(str_value.clone())))
+            }?;
+            Ok(LexItem::LiteralValue(str_value.len(), value))
+        },
+        TokenType::TraitReferenceLiteral => {
+            let str_value = get_value_or_err(current_slice, captures)?;
+            let data = str_value.clone().try_into()
+                .map_err(|_| { ParseError::new(ParseErrors::IllegalVariableName(str_value.to_string())) })?;
+            Ok(LexItem::TraitReference(str_value.len(), data))
+        },
+        TokenType::HexStringLiteral => {
+            let str_value = get_value_or_err(current_slice, captures)?;
+            let byte_vec = hex_bytes(&str_value)
+                .map_err(|x| { ParseError::new(ParseErrors::FailedParsingHexValue(str_value.clone(), x.to_string())) })?;
+            let value = match Value::buff_from(byte_vec) {
+                Ok(parsed) => Ok(parsed),
+                Err(_e) => Err(ParseError::new(ParseErrors::FailedParsingBuffer(str_value.clone())))
+            }?;
+            Ok(LexItem::LiteralValue(str_value.len(), value))
+        },
+        TokenType::StringLiteral => {
+            let str_value = get_value_or_err(current_slice, captures)?;
+            let quote_unescaped = str_value.replace("\\\"","\"");
+            let slash_unescaped = quote_unescaped.replace("\\\\","\\");
+            let byte_vec = slash_unescaped.as_bytes().to_vec();
+            let value = match Value::buff_from(byte_vec) {
+                Ok(parsed) => Ok(parsed),
+                Err(_e) => Err(ParseError::new(ParseErrors::FailedParsingBuffer(str_value.clone())))
+            }?;
+            Ok(LexItem::LiteralValue(str_value.len(), value))
+        },
+    }
+}
+
+/// Span of a token of the source, starting at the given line and column.
+fn token_span(line: u32, column: u32, text: &str) -> Span {
+    let length = text.encode_utf16().count() as u32;
+    Span {
+        start_line: line,
+        start_column: column,
+        end_line: line,
+        end_column: column + length.saturating_sub(1),
     }
 }
 
@@ -335,179 +443,333 @@ fn handle_expression(parse_stack: &mut Vec<(Vec<ParseStackItem>, u32, u32, Parse
     }
 }
 
-pub fn parse_lexed(mut input: Vec<(LexItem, u32, u32)>) -> ParseResult<Vec<PreSymbolicExpression>> {
+fn close_list(list: Vec<ParseStackItem>, span: Span, reporter: &mut ErrorReporter) -> ParseResult<PreSymbolicExpression> {
+    let mut checked_list = Vec::new();
+    for item in list.into_iter() {
+        match item {
+            ParseStackItem::Expression(e) => checked_list.push(e),
+            ParseStackItem::Colon => reporter.report_at(ParseErrors::ColonSeparatorUnexpected, span.clone())?,
+            ParseStackItem::Comma => reporter.report_at(ParseErrors::CommaSeparatorUnexpected, span.clone())?,
+        }
+    }
+    let mut pre_expr = PreSymbolicExpression::list(checked_list.into_boxed_slice());
+    pre_expr.span = span;
+    Ok(pre_expr)
+}
+
+fn close_tuple(tuple_list: Vec<ParseStackItem>, span: Span, reporter: &mut ErrorReporter) -> ParseResult<PreSymbolicExpression> {
+    let mut checked_list = Vec::new();
+    let mut malformed = false;
+    for (index, item) in tuple_list.into_iter().enumerate() {
+        // check that tuple items are (expr, colon, expr, comma)
+        let error = match index % 4 {
+            0 | 2 => {
+                if let ParseStackItem::Expression(_) = item {
+                    None
+                } else {
+                    Some(ParseErrors::TupleItemExpected(index))
+                }
+            },
+            1 => {
+                if let ParseStackItem::Colon = item {
+                    None
+                } else {
+                    Some(ParseErrors::TupleColonExpected(index))
+                }
+            },
+            3 => {
+                if let ParseStackItem::Comma = item {
+                    None
+                } else {
+                    Some(ParseErrors::TupleCommaExpected(index))
+                }
+            },
+            _ => unreachable!("More than four modulos of four."),
+        };
+        // once the structure is broken, only the first error is worth reporting.
+        if let Some(error) = error {
+            if !malformed {
+                reporter.report_at(error, span.clone())?;
+                malformed = true;
+            }
+        }
+        if let ParseStackItem::Expression(e) = item {
+            checked_list.push(e);
+        }
+    }
+    let mut pre_expr = PreSymbolicExpression::tuple(checked_list.into_boxed_slice());
+    pre_expr.span = span;
+    Ok(pre_expr)
+}
+
+/// Closes the lists and tuples left open, reporting an error at their opening delimiter.
+fn close_unterminated(parse_stack: &mut Vec<(Vec<ParseStackItem>, u32, u32, ParseContext)>,
+                      outputs: &mut Vec<PreSymbolicExpression>,
+                      (end_line, end_column): (u32, u32),
+                      reporter: &mut ErrorReporter) -> ParseResult<()> {
+    while let Some((list, start_line, start_column, parse_context)) = parse_stack.pop() {
+        let span = Span { start_line, start_column, end_line, end_column };
+        let pre_expr = match parse_context {
+            ParseContext::CollectList => {
+                reporter.report_at(ParseErrors::ClosingParenthesisExpected, token_span(start_line, start_column, "("))?;
+                close_list(list, span, reporter)?
+            },
+            ParseContext::CollectTuple => {
+                reporter.report_at(ParseErrors::ClosingTupleLiteralExpected, token_span(start_line, start_column, "{"))?;
+                close_tuple(list, span, reporter)?
+            }
+        };
+        handle_expression(parse_stack, outputs, pre_expr);
+    }
+    Ok(())
+}
+
+fn has_unclosed_delimiters(input: &[(LexItem, u32, u32)]) -> bool {
+    let mut depth: usize = 0;
+    for (item, _, _) in input.iter() {
+        match item {
+            LexItem::LeftParen | LexItem::LeftCurly => depth += 1,
+            LexItem::RightParen | LexItem::RightCurly => depth = depth.saturating_sub(1),
+            _ => ()
+        }
+    }
+    depth > 0
+}
+
+fn literal_end_column(column_pos: u32, length: usize) -> u32 {
+    let mut end_column = column_pos + (length as u32);
+    // Avoid underflows on cases like empty strings
+    if length > 0 {
+        end_column -= 1;
+    }
+    end_column
+}
+
+pub fn parse_lexed(input: Vec<(LexItem, u32, u32)>) -> ParseResult<Vec<PreSymbolicExpression>> {
+    parse_lexed_reporting(input, &mut ErrorReporter::fail_fast())
+}
+
+fn parse_lexed_reporting(mut input: Vec<(LexItem, u32, u32)>, reporter: &mut ErrorReporter) -> ParseResult<Vec<PreSymbolicExpression>> {
+    // When recovering from lists left open, a list starting on the first column is taken
+    // as the start of a new top-level expression, so that a missing parenthesis does not
+    // swallow the rest of the contract.
+    let split_on_first_column = reporter.recover && has_unclosed_delimiters(&input);
+
     let mut parse_stack = Vec::new();
 
     let mut output_list = Vec::new();
 
+    let mut last_position = (1, 1);
+
     for (item, line_pos, column_pos) in input.drain(..) {
-        match item {
+        let pre_expr = match item {
             LexItem::LeftParen => {
+                if split_on_first_column && column_pos == 1 {
+                    close_unterminated(&mut parse_stack, &mut output_list, last_position, reporter)?;
+                }
                 // start new list.
                 let new_list = Vec::new();
                 parse_stack.push((new_list, line_pos, column_pos, ParseContext::CollectList));
+                last_position = (line_pos, column_pos);
+                None
             },
             LexItem::RightParen => {
                 // end current list.
-                if let Some((list, start_line, start_column, parse_context)) = parse_stack.pop() {
-                    match parse_context {
-                        ParseContext::CollectList => {
-                            let checked_list: ParseResult<Box<[PreSymbolicExpression]>> = list.into_iter()
-                                .map(|i| {
-                                    match i {
-                                        ParseStackItem::Expression(e) => Ok(e),
-                                        ParseStackItem::Colon => Err(ParseError::new(ParseErrors::ColonSeparatorUnexpected)),
-                                        ParseStackItem::Comma => Err(ParseError::new(ParseErrors::CommaSeparatorUnexpected)),
-                                    } 
-                                })
-                                .collect();
-                            let checked_list = checked_list?;
-                            let mut pre_expr = PreSymbolicExpression::list(checked_list);
-                            pre_expr.set_span(start_line, start_column, line_pos, column_pos);
-                            handle_expression(&mut parse_stack, &mut output_list, pre_expr);
-                        },
-                        ParseContext::CollectTuple => {
-                            return Err(ParseError::new(ParseErrors::ClosingTupleLiteralExpected))
-                        }
+                match parse_stack.pop() {
+                    Some((list, start_line, start_column, ParseContext::CollectList)) => {
+                        let span = Span { start_line, start_column, end_line: line_pos, end_column: column_pos };
+                        Some(close_list(list, span, reporter)?)
+                    },
+                    Some((tuple_list, start_line, start_column, ParseContext::CollectTuple)) => {
+                        reporter.report_at(ParseErrors::ClosingTupleLiteralExpected, token_span(line_pos, column_pos, ")"))?;
+                        let span = Span { start_line, start_column, end_line: line_pos, end_column: column_pos };
+                        Some(close_tuple(tuple_list, span, reporter)?)
+                    },
+                    None => {
+                        reporter.report_at(ParseErrors::ClosingParenthesisUnexpected, token_span(line_pos, column_pos, ")"))?;
+                        None
                     }
-                } else {
-                    return Err(ParseError::new(ParseErrors::ClosingParenthesisUnexpected))
                 }
             },
             LexItem::LeftCurly => {
                 let new_list = Vec::new();
                 parse_stack.push((new_list, line_pos, column_pos, ParseContext::CollectTuple));
+                last_position = (line_pos, column_pos);
+                None
             },
             LexItem::RightCurly => {
-                if let Some((tuple_list, start_line, start_column, parse_context)) = parse_stack.pop() {
-                    match parse_context {
-                        ParseContext::CollectTuple => {
-                            let mut checked_list = Vec::new();
-                            for (index, item) in tuple_list.into_iter().enumerate() {
-                                // check that tuple items are (expr, colon, expr, comma)
-                                match index % 4 {
-                                    0 | 2 => {
-                                        if let ParseStackItem::Expression(e) = item {
-                                            checked_list.push(e);
-                                            Ok(())
-                                        } else {
-                                            Err(ParseErrors::TupleItemExpected(index))
-                                        }
-                                    },
-                                    1 => {
-                                        if let ParseStackItem::Colon = item {
-                                            Ok(())
-                                        } else {
-                                            Err(ParseErrors::TupleColonExpected(index))
-                                        }
-                                    },
-                                    3 => {
-                                        if let ParseStackItem::Comma = item {
-                                            Ok(())
-                                        } else {
-                                            Err(ParseErrors::TupleCommaExpected(index))
-                                        }
-                                    },
-                                    _ => unreachable!("More than four modulos of four."),
-                                }?;
-                            }
-                            let mut pre_expr = PreSymbolicExpression::tuple(checked_list.into_boxed_slice());
-                            pre_expr.set_span(start_line, start_column, line_pos, column_pos);
-                            handle_expression(&mut parse_stack, &mut output_list, pre_expr);
-                        },
-                        ParseContext::CollectList => {
-                            return Err(ParseError::new(ParseErrors::ClosingParenthesisExpected))
-                        }
+                match parse_stack.pop() {
+                    Some((tuple_list, start_line, start_column, ParseContext::CollectTuple)) => {
+                        let span = Span { start_line, start_column, end_line: line_pos, end_column: column_pos };
+                        Some(close_tuple(tuple_list, span, reporter)?)
+                    },
+                    Some((list, start_line, start_column, ParseContext::CollectList)) => {
+                        reporter.report_at(ParseErrors::ClosingParenthesisExpected, token_span(line_pos, column_pos, "}"))?;
+                        let span = Span { start_line, start_column, end_line: line_pos, end_column: column_pos };
+                        Some(close_list(list, span, reporter)?)
+                    },
+                    None => {
+                        reporter.report_at(ParseErrors::ClosingTupleLiteralUnexpected, token_span(line_pos, column_pos, "}"))?;
+                        None
                     }
-                } else {
-                    return Err(ParseError::new(ParseErrors::ClosingTupleLiteralUnexpected))
                 }
             },
             LexItem::Variable(value) => {
                 let end_column = column_pos + (value.len() as u32) - 1;
-                let value = value.clone().try_into()
-                    .map_err(|_| { ParseError::new(ParseErrors::IllegalVariableName(value.to_string())) })?;
-                let mut pre_expr = PreSymbolicExpression::atom(value);
+                let mut pre_expr = match value.clone().try_into() {
+                    Ok(value) => PreSymbolicExpression::atom(value),
+                    Err(_) => {
+                        reporter.report_at(ParseErrors::IllegalVariableName(value.to_string()), token_span(line_pos, column_pos, &value))?;
+                        PreSymbolicExpression::placeholder(value)
+                    }
+                };
                 pre_expr.set_span(line_pos, column_pos, line_pos, end_column);
-                handle_expression(&mut parse_stack, &mut output_list, pre_expr);
+                Some(pre_expr)
             },
             LexItem::LiteralValue(length, value) => {
-                let mut end_column = column_pos + (length as u32);
-                // Avoid underflows on cases like empty strings
-                if length > 0 {
-                    end_column = end_column - 1;
-                }
                 let mut pre_expr = PreSymbolicExpression::atom_value(value);
-                pre_expr.set_span(line_pos, column_pos, line_pos, end_column);
-                handle_expression(&mut parse_stack, &mut output_list, pre_expr);
+                pre_expr.set_span(line_pos, column_pos, line_pos, literal_end_column(column_pos, length));
+                Some(pre_expr)
             },
             LexItem::SugaredContractIdentifier(length, value) => {
-                let mut end_column = column_pos + (length as u32);
-                // Avoid underflows on cases like empty strings
-                if length > 0 {
-                    end_column = end_column - 1;
-                }
                 let mut pre_expr = PreSymbolicExpression::sugared_contract_identifier(value);
-                pre_expr.set_span(line_pos, column_pos, line_pos, end_column);
-                handle_expression(&mut parse_stack, &mut output_list, pre_expr);
+                pre_expr.set_span(line_pos, column_pos, line_pos, literal_end_column(column_pos, length));
+                Some(pre_expr)
             },
             LexItem::SugaredFieldIdentifier(length, contract_name, name) => {
-                let mut end_column = column_pos + (length as u32);
-                // Avoid underflows on cases like empty strings
-                if length > 0 {
-                    end_column = end_column - 1;
-                }
                 let mut pre_expr = PreSymbolicExpression::sugared_field_identifier(contract_name, name);
-                pre_expr.set_span(line_pos, column_pos, line_pos, end_column);
-                handle_expression(&mut parse_stack, &mut output_list, pre_expr);
+                pre_expr.set_span(line_pos, column_pos, line_pos, literal_end_column(column_pos, length));
+                Some(pre_expr)
             },
             LexItem::FieldIdentifier(length, trait_identifier) => {
-                let mut end_column = column_pos + (length as u32);
-                // Avoid underflows on cases like empty strings
-                if length > 0 {
-                    end_column = end_column - 1;
-                }
                 let mut pre_expr = PreSymbolicExpression::field_identifier(trait_identifier);
-                pre_expr.set_span(line_pos, column_pos, line_pos, end_column);
-                handle_expression(&mut parse_stack, &mut output_list, pre_expr);
+                pre_expr.set_span(line_pos, column_pos, line_pos, literal_end_column(column_pos, length));
+                Some(pre_expr)
             },
             LexItem::TraitReference(_length, value) => {
-                let end_column = column_pos + (value.len() as u32) - 1;
-                let value = value.clone().try_into()
-                    .map_err(|_| { ParseError::new(ParseErrors::IllegalVariableName(value.to_string())) })?;
+                // the span covers the name, inside the brackets.
+                let end_column = column_pos + (value.len() as u32);
                 let mut pre_expr = PreSymbolicExpression::trait_reference(value);
-                pre_expr.set_span(line_pos, column_pos, line_pos, end_column);
-                handle_expression(&mut parse_stack, &mut output_list, pre_expr);
-            }
+                pre_expr.set_span(line_pos, column_pos + 1, line_pos, end_column);
+                Some(pre_expr)
+            },
+            LexItem::Placeholder(value) => {
+                let span = token_span(line_pos, column_pos, &value);
+                let mut pre_expr = PreSymbolicExpression::placeholder(value);
+                pre_expr.span = span;
+                Some(pre_expr)
+            },
             LexItem::ColonSeparator => {
                 match parse_stack.last_mut() {
-                    None => return Err(ParseError::new(ParseErrors::ColonSeparatorUnexpected)),
+                    None => reporter.report_at(ParseErrors::ColonSeparatorUnexpected, token_span(line_pos, column_pos, ":"))?,
                     Some((ref mut list, ..)) => {
                         list.push(ParseStackItem::Colon);
                     }
                 };
-            }
+                last_position = (line_pos, column_pos);
+                None
+            },
             LexItem::CommaSeparator => {
                 match parse_stack.last_mut() {
-                    None => return Err(ParseError::new(ParseErrors::CommaSeparatorUnexpected)),
+                    None => reporter.report_at(ParseErrors::CommaSeparatorUnexpected, token_span(line_pos, column_pos, ","))?,
                     Some((ref mut list, ..)) => {
                         list.push(ParseStackItem::Comma);
                     }
                 };
+                last_position = (line_pos, column_pos);
+                None
             },
-            LexItem::Whitespace => ()
+            LexItem::Whitespace | LexItem::Comment(_) => None
         };
+
+        if let Some(pre_expr) = pre_expr {
+            last_position = (pre_expr.span.end_line, pre_expr.span.end_column);
+            handle_expression(&mut parse_stack, &mut output_list, pre_expr);
+        }
     }
 
     // check unfinished stack:
-    if parse_stack.len() > 0 {
-        Err(ParseError::new(ParseErrors::ClosingParenthesisExpected))
-    } else {
-        Ok(output_list)
-    }
+    close_unterminated(&mut parse_stack, &mut output_list, last_position, reporter)?;
+    Ok(output_list)
 }
 
 pub fn parse(input: &str) -> ParseResult<Vec<PreSymbolicExpression>> {
     let lexed = lex(input)?;
     parse_lexed(lexed)
 }
+
+/// Parses the input without stopping at the first error: unparsable input is replaced by
+/// placeholders and unbalanced lists are closed, so that the expressions returned can
+/// still be used while the contract is being edited. All the errors met are returned
+/// alongside.
+pub fn parse_with_recovery(input: &str) -> (Vec<PreSymbolicExpression>, Vec<ParseError>) {
+    let mut reporter = ErrorReporter::recovering();
+    let parsed = lex_reporting(input, &mut reporter)
+        .and_then(|lexed| parse_lexed_reporting(lexed, &mut reporter));
+    let mut errors = reporter.errors;
+    match parsed {
+        Ok(pre_expressions) => (pre_expressions, errors),
+        Err(error) => {
+            errors.push(error);
+            (vec![], errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    fn span(start_line: u32, start_column: u32, end_line: u32, end_column: u32) -> Span {
+        Span { start_line, start_column, end_line, end_column }
+    }
+
+    #[test]
+    fn test_lines_are_tracked_across_blank_lines() {
+        let pre_expressions = parse("(a)\n\n\n(b)").unwrap();
+        assert_eq!(pre_expressions[1].span, span(4, 1, 4, 3));
+    }
+
+    #[test]
+    fn test_fail_fast_errors_have_spans() {
+        let error = parse("(ok u1)\n  (ok u2))").unwrap_err();
+        assert_eq!(error.err, ParseErrors::ClosingParenthesisUnexpected);
+        assert_eq!(error.diagnostic.spans, vec![span(2, 10, 2, 10)]);
+    }
+
+    #[test]
+    fn test_recovery_reports_every_error() {
+        let source = "(define-constant a 1234abc)\n(define-constant b ))\n(define-constant c $$)\n(define-constant d (+ 1 2)";
+        let (pre_expressions, errors) = parse_with_recovery(source);
+        let errors: Vec<_> = errors.into_iter().map(|e| (e.err, e.diagnostic.spans)).collect();
+        assert_eq!(errors, vec![
+            (ParseErrors::SeparatorExpected("abc".to_string()), vec![span(1, 24, 1, 26)]),
+            (ParseErrors::FailedParsingRemainder("$$".to_string()), vec![span(3, 20, 3, 21)]),
+            (ParseErrors::ClosingParenthesisUnexpected, vec![span(2, 21, 2, 21)]),
+            (ParseErrors::ClosingParenthesisExpected, vec![span(4, 1, 4, 1)]),
+        ]);
+
+        assert_eq!(pre_expressions.len(), 4);
+        let placeholder = &pre_expressions[2].match_list().unwrap()[2];
+        assert_eq!(placeholder.pre_expr, PreSymbolicExpressionType::Placeholder("$$".to_string()));
+        assert_eq!(pre_expressions[3].span, span(4, 1, 4, 26));
+        assert_eq!(pre_expressions[3].match_list().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_recovery_keeps_following_definitions_out_of_unclosed_lists() {
+        let source = "(define-private (foo)\n  (begin (ok u1))\n(define-public (bar)\n  (ok u2))";
+        let (pre_expressions, errors) = parse_with_recovery(source);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].err, ParseErrors::ClosingParenthesisExpected);
+        assert_eq!(errors[0].diagnostic.spans, vec![span(1, 1, 1, 1)]);
+        assert_eq!(pre_expressions.len(), 2);
+        assert_eq!(pre_expressions[0].span, span(1, 1, 2, 17));
+        assert_eq!(pre_expressions[1].span, span(3, 1, 4, 10));
+
+        // nested lists on the first column are fine in balanced contracts.
+        let (pre_expressions, errors) = parse_with_recovery("(begin\n(ok u1))");
+        assert!(errors.is_empty());
+        assert_eq!(pre_expressions.len(), 1);
+    }
+}
@@ -81,6 +81,8 @@ impl SugarExpander {
                         return Err(ParseErrors::TraitReferenceUnknown(name.to_string()).into())
                     }                    
                 },
+                // placeholders left by the recovering parser have nothing to expand to.
+                PreSymbolicExpressionType::Placeholder(_) => continue,
             };
             // expr.id will be set by the subsequent expression identifier pass.
             expr.span = pre_expr.span.clone();
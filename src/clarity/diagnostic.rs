@@ -1,26 +1,41 @@
 use super::representations::{Span};
 use std::fmt;
 
-/// In a near future, we can go further in our static analysis and provide different levels 
-/// of diagnostics, such as warnings, hints, best practices, etc.
-#[derive(Debug)]
+/// Errors prevent a contract from being deployed, while the other levels report
+/// findings of the static analysis, such as warnings, hints, best practices, etc.
+#[derive(Debug, Clone)]
 #[derive(PartialEq)]
 pub enum Level {
     Error,
+    Warning,
+    Info,
+    Hint,
 }
 
 pub trait DiagnosableError {
     fn message(&self) -> String;
     fn suggestion(&self) -> Option<String>;
+    /// Stable code of the error, e.g. `CLR-T0032`, documented in `doc/diagnostics.md`.
+    fn code(&self) -> String;
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[derive(PartialEq)]
 pub struct Diagnostic {
     pub level: Level,
+    pub code: Option<String>,
     pub message: String,
     pub spans: Vec<Span>,
     pub suggestion: Option<String>,
+    /// Other places involved, e.g. the definition conflicting with a new one.
+    pub related_information: Vec<RelatedInformation>,
+}
+
+#[derive(Debug, Clone)]
+#[derive(PartialEq)]
+pub struct RelatedInformation {
+    pub span: Span,
+    pub message: String,
 }
 
 impl Diagnostic {
@@ -29,10 +44,23 @@ impl Diagnostic {
         Diagnostic {
             spans: vec![],
             level: Level::Error,
+            code: Some(error.code()),
             message: error.message(),
             suggestion: error.suggestion(),
+            related_information: vec![],
+        }
+    }
+
+    pub fn warning(warning: &dyn DiagnosableError) -> Diagnostic {
+        Diagnostic {
+            level: Level::Warning,
+            .. Diagnostic::err(warning)
         }
     }
+
+    pub fn add_related_information(&mut self, span: Span, message: String) {
+        self.related_information.push(RelatedInformation { span, message });
+    }
 }
 
 impl fmt::Display for Diagnostic {
@@ -0,0 +1,490 @@
+use crate::clarity::ast::cst::{self, CstNode, Trivia};
+use crate::clarity::functions::DefineFunctions;
+use crate::clarity::representations::Span;
+
+/// Lists are kept on one line when they fit in this width.
+const MAX_LINE_WIDTH: usize = 80;
+const INDENT: usize = 2;
+
+enum Node {
+    Atom(String),
+    List(Vec<Entry>),
+    Tuple(Vec<Entry>),
+}
+
+enum Item {
+    Node(Node),
+    Comment(String),
+}
+
+struct Entry {
+    item: Item,
+    /// Comments written after an element stay on the line of that element.
+    trailing: bool,
+    blank_line_before: bool,
+    start_line: u32,
+    end_line: u32,
+}
+
+/// Formats a contract in the canonical style, keeping its comments. Contracts which
+/// can not be parsed are left alone.
+pub fn format_source(source: &str) -> Option<String> {
+    let entries = build_tree(source)?;
+    Some(format_top_level(&entries))
+}
+
+/// Formats the top-level expressions found on the given lines, 1-based and inclusive.
+/// Returns the byte range of the lines they cover, with the text replacing them.
+pub fn format_lines(source: &str, first_line: u32, last_line: u32) -> Option<(usize, usize, String)> {
+    let entries = build_tree(source)?;
+    let mut selected = entries.iter()
+        .position(|entry| entry.end_line >= first_line && entry.start_line <= last_line)?;
+    let mut selected_end = entries.iter()
+        .rposition(|entry| entry.end_line >= first_line && entry.start_line <= last_line)?;
+    // expressions sharing a line with the selection are formatted along.
+    while selected > 0 && entries[selected - 1].end_line >= entries[selected].start_line {
+        selected -= 1;
+    }
+    while selected_end + 1 < entries.len() && entries[selected_end + 1].start_line <= entries[selected_end].end_line {
+        selected_end += 1;
+    }
+
+    let line_starts = line_starts(source);
+    let start = line_starts[entries[selected].start_line as usize - 1];
+    let end = line_starts.get(entries[selected_end].end_line as usize)
+        .cloned()
+        .unwrap_or(source.len());
+    Some((start, end, format_top_level(&entries[selected..=selected_end])))
+}
+
+fn line_starts(source: &str) -> Vec<usize> {
+    let mut starts = vec![0];
+    starts.extend(source.match_indices('\n').map(|(index, _)| index + 1));
+    starts
+}
+
+/// Builds the tree of the expressions and comments of the source, or None when the
+/// source does not lex, or has unbalanced delimiters.
+fn build_tree(source: &str) -> Option<Vec<Entry>> {
+    let tree = cst::parse(source);
+    let mut previous_line = 0;
+    build_entries(&tree.nodes, &tree.trailing_trivia, &mut previous_line)
+}
+
+/// Entries of the nodes, and of the comments before them and before the closing
+/// delimiter. `previous_line` is the line the last token seen ends on.
+fn build_entries(nodes: &[CstNode], closing_trivia: &[Trivia], previous_line: &mut u32) -> Option<Vec<Entry>> {
+    let mut entries = vec![];
+    for node in nodes.iter() {
+        push_comments(&mut entries, &node.first_token().leading_trivia, previous_line);
+        let (item, span) = match node {
+            CstNode::Atom(token, _) => (Item::Node(Node::Atom(token.text.clone())), token.span.clone()),
+            CstNode::List(delimited) | CstNode::Tuple(delimited) => {
+                let close = delimited.close.as_ref()?;
+                let blank_line_before = is_after_blank_line(&delimited.open.span, *previous_line);
+                *previous_line = delimited.open.span.end_line;
+                let children = build_entries(&delimited.children, &close.leading_trivia, previous_line)?;
+                let node = match node {
+                    CstNode::Tuple(_) if !is_valid_tuple(&children) => return None,
+                    CstNode::Tuple(_) => Node::Tuple(children),
+                    _ => Node::List(children),
+                };
+                entries.push(Entry {
+                    item: Item::Node(node),
+                    trailing: false,
+                    blank_line_before,
+                    start_line: delimited.open.span.start_line,
+                    end_line: close.span.end_line,
+                });
+                *previous_line = close.span.end_line;
+                continue;
+            },
+            // separators are written back by the formatter.
+            CstNode::Separator(_) => continue,
+            CstNode::Error(_) => return None,
+        };
+        entries.push(Entry {
+            item,
+            trailing: false,
+            blank_line_before: is_after_blank_line(&span, *previous_line),
+            start_line: span.start_line,
+            end_line: span.end_line,
+        });
+        *previous_line = span.end_line;
+    }
+    push_comments(&mut entries, closing_trivia, previous_line);
+    Some(entries)
+}
+
+fn push_comments(entries: &mut Vec<Entry>, trivia: &[Trivia], previous_line: &mut u32) {
+    for trivia in trivia.iter() {
+        if let Trivia::Comment(comment, span) = trivia {
+            entries.push(Entry {
+                item: Item::Comment(comment.trim_end().to_string()),
+                trailing: span.start_line == *previous_line,
+                blank_line_before: is_after_blank_line(span, *previous_line),
+                start_line: span.start_line,
+                end_line: span.end_line,
+            });
+            *previous_line = span.end_line;
+        }
+    }
+}
+
+fn is_after_blank_line(span: &Span, previous_line: u32) -> bool {
+    previous_line > 0 && span.start_line > previous_line + 1
+}
+
+/// Tuples are pairs of keys and values, with comments only in between pairs.
+fn is_valid_tuple(entries: &[Entry]) -> bool {
+    let mut position = 0;
+    for entry in entries.iter() {
+        match entry.item {
+            Item::Comment(_) if position % 2 == 1 => return false,
+            Item::Comment(_) => {},
+            Item::Node(_) => position += 1,
+        }
+    }
+    position % 2 == 0
+}
+
+fn format_top_level(entries: &[Entry]) -> String {
+    let mut output = String::new();
+    for (index, entry) in entries.iter().enumerate() {
+        if index > 0 && entry.trailing {
+            output.push(' ');
+        } else if index > 0 {
+            output.push('\n');
+            if entry.blank_line_before {
+                output.push('\n');
+            }
+        }
+        output.push_str(&format_item(&entry.item, 0));
+    }
+    output.push('\n');
+    output
+}
+
+fn format_item(item: &Item, column: usize) -> String {
+    match item {
+        Item::Comment(comment) => comment.clone(),
+        Item::Node(node) => format_node(node, column),
+    }
+}
+
+/// Formats a node starting at the given column.
+fn format_node(node: &Node, column: usize) -> String {
+    match node {
+        Node::Atom(text) => text.clone(),
+        Node::List(entries) => format_list(entries, column),
+        Node::Tuple(entries) => format_tuple(entries, column),
+    }
+}
+
+/// The node on a single line, unless it contains comments.
+fn flat(node: &Node) -> Option<String> {
+    match node {
+        Node::Atom(text) => Some(text.clone()),
+        Node::List(entries) => flat_list(entries),
+        Node::Tuple(entries) => flat_tuple(entries),
+    }
+}
+
+fn flat_elements(entries: &[Entry]) -> Option<Vec<String>> {
+    entries.iter().map(|entry| match entry.item {
+        Item::Node(ref node) => flat(node),
+        Item::Comment(_) => None,
+    }).collect()
+}
+
+fn flat_list(entries: &[Entry]) -> Option<String> {
+    Some(format!("({})", flat_elements(entries)?.join(" ")))
+}
+
+fn flat_tuple(entries: &[Entry]) -> Option<String> {
+    let pairs: Vec<_> = flat_elements(entries)?.chunks(2).map(|pair| pair.join(": ")).collect();
+    Some(format!("{{{}}}", pairs.join(", ")))
+}
+
+fn fits(text: &str, column: usize) -> bool {
+    column + text.len() <= MAX_LINE_WIDTH
+}
+
+/// Column at which text appended to the output would start.
+fn current_column(output: &str, column: usize) -> usize {
+    match output.rfind('\n') {
+        Some(line_break) => output.len() - line_break - 1,
+        None => column + output.len(),
+    }
+}
+
+fn new_line(output: &mut String, column: usize, blank_line_before: bool) {
+    output.push('\n');
+    if blank_line_before {
+        output.push('\n');
+    }
+    output.push_str(&" ".repeat(column));
+}
+
+fn close(output: &mut String, entries: &[Entry], column: usize, delimiter: char) {
+    // a comment ends the line, the delimiter can not follow it.
+    if let Some(Entry { item: Item::Comment(_), .. }) = entries.last() {
+        new_line(output, column, false);
+    }
+    output.push(delimiter);
+}
+
+fn head_atom(entries: &[Entry]) -> Option<&str> {
+    match entries.first()?.item {
+        Item::Node(Node::Atom(ref head)) => Some(head),
+        _ => None
+    }
+}
+
+fn format_list(entries: &[Entry], column: usize) -> String {
+    let head = match head_atom(entries) {
+        Some(head) => head,
+        None => return format_sequence(entries, column, false),
+    };
+
+    // functions and local bindings always have their body on lines of their own.
+    let is_function = matches!(DefineFunctions::lookup_by_name(head),
+        Some(DefineFunctions::PublicFunction) | Some(DefineFunctions::PrivateFunction) |
+        Some(DefineFunctions::ReadOnlyFunction));
+    let always_breaks = is_function || head == "let" || head == "match";
+    if !always_breaks {
+        if let Some(text) = flat_list(entries) {
+            if fits(&text, column) {
+                return text;
+            }
+        }
+    }
+
+    // arguments kept on the line of the function, e.g. the contract and function
+    // names of `contract-call?`.
+    let header_length = match head {
+        "begin" => 0,
+        "contract-call?" => 2,
+        _ => 1,
+    };
+
+    let mut output = format!("({}", head);
+    let mut index = 1;
+    while index <= header_length && index < entries.len() {
+        let node = match entries[index] {
+            Entry { item: Item::Node(ref node), blank_line_before: false, .. } => node,
+            _ => break
+        };
+        output.push(' ');
+        let node_column = current_column(&output, column);
+        let text = match (head, node) {
+            // bindings go on a line each.
+            ("let", Node::List(bindings)) => format_sequence(bindings, node_column, bindings.len() > 1),
+            _ => format_node(node, node_column),
+        };
+        output.push_str(&text);
+        index += 1;
+    }
+
+    // `match` branches follow their binding, e.g. `some-value (ok some-value)`.
+    let arms: Vec<_> = entries[index..].iter().filter(|entry| !is_comment(entry)).collect();
+    let bindings = match (head, arms.len()) {
+        ("match", 3) => vec![0],
+        ("match", 4) => vec![0, 2],
+        _ => vec![],
+    };
+
+    let body_column = column + INDENT;
+    let mut arm = 0;
+    let mut follows_binding = false;
+    for entry in entries[index..].iter() {
+        match entry.item {
+            Item::Comment(ref comment) if entry.trailing => {
+                output.push(' ');
+                output.push_str(comment);
+                follows_binding = false;
+            },
+            Item::Comment(ref comment) => {
+                new_line(&mut output, body_column, entry.blank_line_before);
+                output.push_str(comment);
+                follows_binding = false;
+            },
+            Item::Node(ref node) => {
+                if follows_binding {
+                    output.push(' ');
+                } else {
+                    new_line(&mut output, body_column, entry.blank_line_before);
+                }
+                let node_column = current_column(&output, column);
+                output.push_str(&format_node(node, node_column));
+                follows_binding = bindings.contains(&arm);
+                arm += 1;
+            },
+        }
+    }
+
+    close(&mut output, entries, column, ')');
+    output
+}
+
+/// Formats a list without a function, e.g. bindings or arguments, with its elements
+/// aligned after the parenthesis.
+fn format_sequence(entries: &[Entry], column: usize, always_breaks: bool) -> String {
+    if !always_breaks {
+        if let Some(text) = flat_list(entries) {
+            if fits(&text, column) {
+                return text;
+            }
+        }
+    }
+
+    let element_column = column + 1;
+    let mut output = String::from("(");
+    for (index, entry) in entries.iter().enumerate() {
+        if index > 0 && entry.trailing {
+            output.push(' ');
+        } else if index > 0 {
+            new_line(&mut output, element_column, entry.blank_line_before);
+        }
+        output.push_str(&format_item(&entry.item, element_column));
+    }
+    close(&mut output, entries, column, ')');
+    output
+}
+
+fn format_tuple(entries: &[Entry], column: usize) -> String {
+    if let Some(text) = flat_tuple(entries) {
+        if fits(&text, column) {
+            return text;
+        }
+    }
+
+    let entry_column = column + 1;
+    let pair_count = entries.iter().filter(|entry| !is_comment(entry)).count() / 2;
+    let mut output = String::from("{");
+    let mut is_first_line = true;
+    let mut position = 0;
+    for entry in entries.iter() {
+        match entry.item {
+            Item::Comment(ref comment) if entry.trailing && !is_first_line => {
+                output.push(' ');
+                output.push_str(comment);
+            },
+            Item::Comment(ref comment) => {
+                if !is_first_line {
+                    new_line(&mut output, entry_column, entry.blank_line_before);
+                }
+                output.push_str(comment);
+                is_first_line = false;
+            },
+            Item::Node(ref node) if position % 2 == 0 => {
+                if !is_first_line {
+                    new_line(&mut output, entry_column, entry.blank_line_before);
+                }
+                output.push_str(&format_node(node, entry_column));
+                output.push_str(": ");
+                is_first_line = false;
+                position += 1;
+            },
+            Item::Node(ref node) => {
+                let value_column = current_column(&output, column);
+                output.push_str(&format_node(node, value_column));
+                position += 1;
+                if position / 2 < pair_count {
+                    output.push(',');
+                }
+            },
+        }
+    }
+    close(&mut output, entries, column, '}');
+    output
+}
+
+fn is_comment(entry: &Entry) -> bool {
+    match entry.item {
+        Item::Comment(_) => true,
+        Item::Node(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_layout() {
+        let source = "(define-data-var   counter uint u0)\n\n\n\
+                      (define-public (add (step uint)) (let ((current (var-get counter)) (next (+ current step)))\n\
+                      (var-set counter next) (ok next)))\n\
+                      (define-read-only (get (who principal)) (match (map-get? balances {owner: who}) entry (ok (get amount entry)) (err u1)))";
+        let expected = "(define-data-var counter uint u0)\n\
+                        \n\
+                        (define-public (add (step uint))\n\
+                        \x20 (let ((current (var-get counter))\n\
+                        \x20       (next (+ current step)))\n\
+                        \x20   (var-set counter next)\n\
+                        \x20   (ok next)))\n\
+                        (define-read-only (get (who principal))\n\
+                        \x20 (match (map-get? balances {owner: who})\n\
+                        \x20   entry (ok (get amount entry))\n\
+                        \x20   (err u1)))\n";
+        assert_eq!(format_source(source), Some(expected.to_string()));
+        assert_eq!(format_source(expected), Some(expected.to_string()));
+    }
+
+    #[test]
+    fn test_long_lists_and_tuples_break() {
+        let source = "(define-map balances {owner: principal} {amount: uint, locked: bool, unlock-height: uint, memo: (buff 34)})\n\
+                      (define-public (call) (ok (contract-call? .other-contract some-long-function-name u1 u2 (some-other-function u3))))\n\
+                      (define-constant info {name: \"a long descriptive name\", symbol: \"SYMBOL\", decimals: u8, supply: u1000000})";
+        let expected = "(define-map balances\n\
+                        \x20 {owner: principal}\n\
+                        \x20 {amount: uint, locked: bool, unlock-height: uint, memo: (buff 34)})\n\
+                        (define-public (call)\n\
+                        \x20 (ok (contract-call? .other-contract some-long-function-name\n\
+                        \x20       u1\n\
+                        \x20       u2\n\
+                        \x20       (some-other-function u3))))\n\
+                        (define-constant info\n\
+                        \x20 {name: \"a long descriptive name\",\n\
+                        \x20  symbol: \"SYMBOL\",\n\
+                        \x20  decimals: u8,\n\
+                        \x20  supply: u1000000})\n";
+        assert_eq!(format_source(source), Some(expected.to_string()));
+    }
+
+    #[test]
+    fn test_comments_are_preserved() {
+        let source = ";; counter\n\
+                      (define-data-var counter uint u0) ;; the count\n\
+                      (define-public (increment) ;; by one\n\
+                      \x20   ;; read it first\n\
+                      \x20   (begin (var-set counter (+ (var-get counter) u1)) ;; add\n\
+                      \x20   (ok true) ;; done\n\
+                      ))";
+        let expected = ";; counter\n\
+                        (define-data-var counter uint u0) ;; the count\n\
+                        (define-public (increment) ;; by one\n\
+                        \x20 ;; read it first\n\
+                        \x20 (begin\n\
+                        \x20   (var-set counter (+ (var-get counter) u1)) ;; add\n\
+                        \x20   (ok true) ;; done\n\
+                        \x20 ))\n";
+        assert_eq!(format_source(source), Some(expected.to_string()));
+        assert_eq!(format_source(expected), Some(expected.to_string()));
+    }
+
+    #[test]
+    fn test_invalid_sources_are_not_formatted() {
+        assert_eq!(format_source("(define-public (f) (ok u1)"), None);
+        assert_eq!(format_source("(define-public (f) (ok u1)))"), None);
+        assert_eq!(format_source("{a: 1, b}"), None);
+    }
+
+    #[test]
+    fn test_format_lines() {
+        let source = "(define-constant a   u1)\n(define-constant b   u2) (define-constant c u3)\n(define-constant d   u4)\n";
+        assert_eq!(format_lines(source, 2, 2), Some((25, 73, "(define-constant b u2)\n(define-constant c u3)\n".to_string())));
+        assert_eq!(format_lines(source, 5, 6), None);
+    }
+}
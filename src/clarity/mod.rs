@@ -12,6 +12,7 @@ pub mod docs;
 pub mod analysis;
 pub mod util;
 pub mod functions;
+pub mod formatter;
 
 
 pub use types::Value;
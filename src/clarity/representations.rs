@@ -81,6 +81,8 @@ pub enum PreSymbolicExpressionType {
     SugaredFieldIdentifier(ContractName, ClarityName),
     FieldIdentifier(TraitIdentifier),
     TraitReference(ClarityName),
+    // Stands in for input the parser could not make sense of, when parsing with recovery.
+    Placeholder(String),
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
@@ -199,6 +201,13 @@ impl PreSymbolicExpression {
         }
     }
 
+    pub fn placeholder(val: String) -> PreSymbolicExpression {
+        PreSymbolicExpression {
+            pre_expr: PreSymbolicExpressionType::Placeholder(val),
+            .. PreSymbolicExpression::cons()
+        }
+    }
+
     pub fn match_trait_reference(&self) -> Option<&ClarityName> {
         if let PreSymbolicExpressionType::TraitReference(ref value) = self.pre_expr {
             Some(value)
@@ -47,6 +47,17 @@ impl DefineFunctions {
         let define_type = DefineFunctions::lookup_by_name(function_name)?;
         Some((define_type, args))
     }
+
+    /// Returns the name introduced by a top-level define, e.g. `foo` for
+    /// `(define-private (foo) ...)`, or `counter` for `(define-data-var counter ...)`.
+    pub fn try_parse_defined_name(expression: &SymbolicExpression) -> Option<&ClarityName> {
+        let (_, args) = DefineFunctions::try_parse(expression)?;
+        let defined_name = match args.first()?.match_list() {
+            Some(signature) => signature.first()?,
+            None => &args[0]
+        };
+        defined_name.match_atom()
+    }
 }
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
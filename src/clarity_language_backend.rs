@@ -2,53 +2,298 @@ use async_trait::async_trait;
 
 use tokio;
 
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use tower_lsp::jsonrpc::Result;
+use jsonrpc_core::{IoHandler, Params};
+use tower_lsp::jsonrpc::{Error, Result};
 use tower_lsp::lsp_types::*;
+use tower_lsp::lsp_types::request::GotoDefinitionResponse;
 use tower_lsp::{LanguageServer, LspService, Client, Server};
 
 use std::collections::HashMap;
 use std::fs;
-
-use super::clarity::functions::{
-    NativeFunctions, 
-    DefineFunctions, 
-    NativeVariables, 
-    BlockInfoProperty};
-
-use super::clarity::docs::{
-    make_api_reference, 
-    make_define_reference, 
-    make_keyword_reference};
-
-use super::clarity::analysis::AnalysisDatabase;
-use super::clarity::types::QualifiedContractIdentifier;
-use super::clarity::{ast, analysis};
-use super::clarity::costs::LimitedCostTracker;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::clarity::ast::parser;
+use super::clarity::diagnostic::{Diagnostic as ClarityDiagnostic, Level};
+use super::clarity::formatter;
+use super::completion::completion_items;
+use super::contract_state::ContractState;
+use super::document_symbols::document_symbols;
+use super::hover::hover_at;
+use super::quick_fixes::{quick_fixes, warning_fixes};
+use super::spans::span_to_range;
+use super::rename;
+use super::semantic_tokens;
+use super::signature_help::signature_help;
+use super::symbol_index::{SymbolIndex, DefinitionKind};
+use super::workspace;
+use super::workspace_symbols::WorkspaceSymbols;
+use super::text_document::TextDocument;
+
+const SEMANTIC_TOKENS_PROPOSED_METHOD: &str = "textDocument/semanticTokens";
+const SEMANTIC_TOKENS_FULL_METHOD: &str = "textDocument/semanticTokens/full";
+const SEMANTIC_TOKENS_RANGE_METHOD: &str = "textDocument/semanticTokens/range";
+
+/// Documentation of the diagnostic codes, with an anchor per code.
+const DIAGNOSTIC_CODES_URL: &str = "https://github.com/lgalabru/clarity-lsp/blob/master/doc/diagnostics.md";
+
+/// Delay between the last keystroke and the analysis of the buffer.
+const DIAGNOSTICS_DEBOUNCE_DELAY: Duration = Duration::from_millis(300);
 
 #[derive(Debug, Default)]
 pub struct ClarityLanguageBackend {
-    tracked_documents: HashMap<String, String>,
+    // shared with the handlers of the methods `LanguageServer` does not cover.
+    tracked_documents: Arc<Mutex<HashMap<String, TextDocument>>>,
+    workspace_roots: Mutex<Vec<PathBuf>>,
+    workspace_symbols: Mutex<WorkspaceSymbols>,
 }
 
 impl ClarityLanguageBackend {
 
     pub fn new() -> Self {
         Self {
-            tracked_documents: HashMap::new(),
+            tracked_documents: Arc::new(Mutex::new(HashMap::new())),
+            workspace_roots: Mutex::new(Vec::new()),
+            workspace_symbols: Mutex::new(WorkspaceSymbols::default()),
+        }
+    }
+
+    /// Handlers of the semantic tokens requests, which `LanguageServer` does not cover
+    /// yet. Both the proposed and the final method names are served.
+    pub fn extension_methods(&self) -> IoHandler {
+        let mut handler = IoHandler::new();
+
+        let documents = self.tracked_documents.clone();
+        let full = move |params: Params| {
+            let params: SemanticTokensParams = params.parse()?;
+            // the lock is released before the analysis.
+            let contract = documents.lock().unwrap().get(&params.text_document.uri.to_string())
+                .map(|document| document.text().to_string());
+            let tokens = contract.map(|contract| SemanticTokens {
+                result_id: None,
+                data: semantic_tokens::semantic_tokens(&contract, None),
+            });
+            Ok(serde_json::to_value(tokens).unwrap())
+        };
+        handler.add_method(SEMANTIC_TOKENS_PROPOSED_METHOD, full.clone());
+        handler.add_method(SEMANTIC_TOKENS_FULL_METHOD, full);
+
+        let documents = self.tracked_documents.clone();
+        handler.add_method(SEMANTIC_TOKENS_RANGE_METHOD, move |params: Params| {
+            let params: SemanticTokensRangeParams = params.parse()?;
+            let contract = documents.lock().unwrap().get(&params.text_document.uri.to_string())
+                .map(|document| document.text().to_string());
+            let tokens = contract.map(|contract| SemanticTokens {
+                result_id: None,
+                data: semantic_tokens::semantic_tokens(&contract, Some(&params.range)),
+            });
+            Ok(serde_json::to_value(tokens).unwrap())
+        });
+
+        handler
+    }
+
+    fn open_document(&self, uri: &Url, text: String) {
+        let mut documents = self.tracked_documents.lock().unwrap();
+        documents.insert(uri.to_string(), TextDocument::new(text));
+    }
+
+    fn update_document(&self, uri: &Url, changes: &[TextDocumentContentChangeEvent]) {
+        let mut documents = self.tracked_documents.lock().unwrap();
+        if let Some(document) = documents.get_mut(&uri.to_string()) {
+            for change in changes.iter() {
+                document.apply_change(change);
+            }
+        }
+    }
+
+    fn remove_document(&self, uri: &Url) {
+        let mut documents = self.tracked_documents.lock().unwrap();
+        documents.remove(&uri.to_string());
+    }
+
+    fn get_document(&self, uri: &Url) -> Option<String> {
+        let documents = self.tracked_documents.lock().unwrap();
+        documents.get(&uri.to_string()).map(|document| document.text().to_string())
+    }
+
+    /// Reads a contract of the workspace, preferring the editor buffer when it is open.
+    fn read_contract(&self, uri: &Url) -> Option<String> {
+        self.get_document(uri).or_else(|| {
+            let path = uri.to_file_path().ok()?;
+            fs::read_to_string(path).ok()
+        })
+    }
+
+    /// Contracts of the workspace folders, along with the ones opened from elsewhere.
+    fn workspace_contracts(&self) -> Vec<Url> {
+        let mut contracts = workspace::find_contracts(&self.workspace_roots.lock().unwrap());
+        let documents = self.tracked_documents.lock().unwrap();
+        for uri in documents.keys() {
+            if let Ok(uri) = Url::parse(uri) {
+                if !contracts.contains(&uri) {
+                    contracts.push(uri);
+                }
+            }
+        }
+        contracts
+    }
+
+    /// Re-indexes the symbols of a contract, or drops them when it no longer exists.
+    fn index_contract(&self, uri: &Url) {
+        match self.read_contract(uri) {
+            Some(contract) => self.index_contract_state(uri, &ContractState::new(&contract)),
+            None => self.workspace_symbols.lock().unwrap().remove(uri)
+        }
+    }
+
+    fn index_contract_state(&self, uri: &Url, state: &ContractState) {
+        self.workspace_symbols.lock().unwrap().update(uri.clone(), state);
+    }
+
+    fn publish_diagnostics(&self, client: &Client, uri: Url) {
+        if let Some(contract) = self.get_document(&uri) {
+            let state = ContractState::new(&contract);
+            self.index_contract_state(&uri, &state);
+            publish_coded_diagnostics(client, uri.clone(), check_contract(&uri, state));
+        }
+    }
+
+    /// Waits for the buffer to settle before analysing it: if another edit lands
+    /// during the delay, the analysis is left to the task handling that edit.
+    async fn publish_diagnostics_debounced(&self, client: &Client, uri: Url) {
+        let snapshot = match self.get_document(&uri) {
+            Some(contract) => contract,
+            None => return
+        };
+
+        tokio::time::delay_for(DIAGNOSTICS_DEBOUNCE_DELAY).await;
+
+        match self.get_document(&uri) {
+            Some(ref contract) if contract == &snapshot => {
+                let state = ContractState::new(contract);
+                self.index_contract_state(&uri, &state);
+                publish_coded_diagnostics(client, uri.clone(), check_contract(&uri, state));
+            },
+            _ => {}
         }
     }
 }
 
+fn check_contract(uri: &Url, state: ContractState) -> Vec<ClarityDiagnostic> {
+    let suppressions = state.suppressions;
+    // check errors on a partially parsed contract are mostly noise.
+    let diagnostics: Vec<_> = if state.parse_errors.is_empty() {
+        state.check_errors.into_iter().map(|check_error| check_error.diagnostic)
+            .chain(state.check_warnings.into_iter().map(|check_warning| check_warning.diagnostic))
+            .collect()
+    } else {
+        state.parse_errors.into_iter().map(|parse_error| parse_error.diagnostic).collect()
+    };
+    diagnostics.into_iter()
+        .filter(|diagnostic| !suppressions.is_suppressed(diagnostic))
+        .collect()
+}
+
+/// `textDocument/publishDiagnostics`, sending the `codeDescription` of the diagnostics,
+/// which lsp-types does not know yet.
+enum PublishCodedDiagnostics {}
+
+impl notification::Notification for PublishCodedDiagnostics {
+    type Params = PublishCodedDiagnosticsParams;
+    const METHOD: &'static str = "textDocument/publishDiagnostics";
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PublishCodedDiagnosticsParams {
+    uri: Url,
+    diagnostics: Vec<CodedDiagnostic>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CodedDiagnostic {
+    #[serde(flatten)]
+    diagnostic: Diagnostic,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code_description: Option<CodeDescription>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CodeDescription {
+    href: Url,
+}
+
+fn publish_coded_diagnostics(client: &Client, uri: Url, diagnostics: Vec<ClarityDiagnostic>) {
+    let diagnostics = diagnostics.into_iter()
+        .map(|diagnostic| CodedDiagnostic {
+            code_description: diagnostic.code.as_ref().and_then(|code| code_description(code)),
+            diagnostic: make_diagnostic(&uri, diagnostic),
+        })
+        .collect();
+    client.send_custom_notification::<PublishCodedDiagnostics>(PublishCodedDiagnosticsParams { uri, diagnostics });
+}
+
+fn code_description(code: &str) -> Option<CodeDescription> {
+    let href = Url::parse(&format!("{}#{}", DIAGNOSTIC_CODES_URL, code.to_lowercase())).ok()?;
+    Some(CodeDescription { href })
+}
+
+fn make_diagnostic(uri: &Url, diagnostic: ClarityDiagnostic) -> Diagnostic {
+    let range = match diagnostic.spans.len() {
+        0 => Range::default(),
+        _ => span_to_range(&diagnostic.spans[0])
+    };
+    let severity = match diagnostic.level {
+        Level::Error => DiagnosticSeverity::Error,
+        Level::Warning => DiagnosticSeverity::Warning,
+        Level::Info => DiagnosticSeverity::Information,
+        Level::Hint => DiagnosticSeverity::Hint,
+    };
+    let related_information: Vec<_> = diagnostic.related_information.into_iter()
+        .map(|related| DiagnosticRelatedInformation {
+            location: Location::new(uri.clone(), span_to_range(&related.span)),
+            message: related.message,
+        })
+        .collect();
+    Diagnostic {
+        range,
+        severity: Some(severity),
+        code: diagnostic.code.map(NumberOrString::String),
+        source: Some("clarity".to_string()),
+        message: match diagnostic.suggestion {
+            Some(suggestion) => format!("{}\n{}", diagnostic.message, suggestion),
+            None => diagnostic.message
+        },
+        related_information: if related_information.is_empty() {
+            None
+        } else {
+            Some(related_information)
+        },
+        tags: None,
+    }
+}
+
 #[async_trait]
 impl LanguageServer for ClarityLanguageBackend {
 
-    fn initialize(&self, _: &Client, _: InitializeParams) -> Result<InitializeResult> {
+    fn initialize(&self, _: &Client, params: InitializeParams) -> Result<InitializeResult> {
+        let roots = match params.workspace_folders {
+            Some(folders) => folders.into_iter().map(|folder| folder.uri).collect(),
+            None => params.root_uri.into_iter().collect::<Vec<_>>()
+        };
+        *self.workspace_roots.lock().unwrap() = roots.iter()
+            .filter_map(|uri| uri.to_file_path().ok())
+            .collect();
+
         Ok(InitializeResult {
             server_info: None,
             capabilities: ServerCapabilities {
                 text_document_sync: Some(TextDocumentSyncCapability::Kind(
-                    TextDocumentSyncKind::Full,
+                    TextDocumentSyncKind::Incremental,
                 )),
                 completion_provider: Some(CompletionOptions {
                     resolve_provider: Some(false),
@@ -56,14 +301,58 @@ impl LanguageServer for ClarityLanguageBackend {
                     work_done_progress_options: Default::default(),
                 }),
                 type_definition_provider: None,
-                hover_provider: Some(false),
+                hover_provider: Some(true),
+                signature_help_provider: Some(SignatureHelpOptions {
+                    trigger_characters: Some(vec!["(".to_string(), " ".to_string()]),
+                    retrigger_characters: None,
+                    work_done_progress_options: Default::default(),
+                }),
                 declaration_provider: Some(false),
+                definition_provider: Some(true),
+                references_provider: Some(true),
+                document_highlight_provider: Some(true),
+                document_symbol_provider: Some(true),
+                workspace_symbol_provider: Some(true),
+                code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
+                    code_action_kinds: Some(vec![code_action_kind::QUICKFIX.to_string()]),
+                    work_done_progress_options: Default::default(),
+                })),
+                document_formatting_provider: Some(true),
+                document_range_formatting_provider: Some(true),
+                semantic_tokens_provider: Some(SemanticTokensServerCapabilities::from(SemanticTokensOptions {
+                    work_done_progress_options: Default::default(),
+                    legend: semantic_tokens::legend(),
+                    range_provider: Some(true),
+                    document_provider: Some(SemanticTokensDocumentProvider::Bool(true)),
+                })),
+                rename_provider: Some(RenameProviderCapability::Options(RenameOptions {
+                    prepare_provider: Some(true),
+                    work_done_progress_options: Default::default(),
+                })),
                 ..ServerCapabilities::default()
             },
         })
     }
 
     async fn initialized(&self, client: &Client, _: InitializedParams) {
+        for uri in self.workspace_contracts() {
+            self.index_contract(&uri);
+        }
+
+        let watchers = DidChangeWatchedFilesRegistrationOptions {
+            watchers: vec![FileSystemWatcher {
+                glob_pattern: "**/*.clar".to_string(),
+                kind: None,
+            }],
+        };
+        let registration = Registration {
+            id: "clarity-contracts-watcher".to_string(),
+            method: "workspace/didChangeWatchedFiles".to_string(),
+            register_options: serde_json::to_value(watchers).ok(),
+        };
+        if let Err(error) = client.register_capability(vec![registration]).await {
+            client.log_message(MessageType::Warning, format!("Unable to watch contract files: {}", error.message));
+        }
     }
 
     async fn shutdown(&self) -> Result<()> {
@@ -78,236 +367,307 @@ impl LanguageServer for ClarityLanguageBackend {
         Ok(None)
     }
 
-    async fn completion(&self, _: CompletionParams) -> Result<Option<CompletionResponse>> {
-
-        let native_functions: Vec<CompletionItem> = NativeFunctions::ALL
-            .iter()
-            .map(|func| {
-                let api = make_api_reference(&func);
-                CompletionItem {
-                    label: api.name.to_string(),
-                    kind: Some(CompletionItemKind::Function),
-                    detail: Some(api.name.to_string()),
-                    documentation: Some(Documentation::MarkupContent(MarkupContent {
-                        kind: MarkupKind::Markdown,
-                        value: api.description.to_string(),
-                    })),
-                    deprecated: None,
-                    preselect: None,
-                    sort_text: None,
-                    filter_text: None,
-                    insert_text: Some(api.snippet.clone()),
-                    insert_text_format: Some(InsertTextFormat::Snippet),
-                    text_edit: None,
-                    additional_text_edits: None,
-                    command: None,
-                    data: None,
-                    tags: None,
-                }})
-            .collect();
-        
-        let define_functions: Vec<CompletionItem> = DefineFunctions::ALL
-            .iter()
-            .map(|func| {
-                let api = make_define_reference(&func);
-                CompletionItem {
-                    label: api.name.to_string(),
-                    kind: Some(CompletionItemKind::Class),
-                    detail: Some(api.name.to_string()),
-                    documentation: Some(Documentation::MarkupContent(MarkupContent {
-                        kind: MarkupKind::Markdown,
-                        value: api.description.to_string(),
-                    })),
-                    deprecated: None,
-                    preselect: None,
-                    sort_text: None,
-                    filter_text: None,
-                    insert_text: Some(api.snippet.clone()),
-                    insert_text_format: Some(InsertTextFormat::Snippet),
-                    text_edit: None,
-                    additional_text_edits: None,
-                    command: None,
-                    data: None,
-                    tags: None,
-                }})
-            .collect();
+    async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
+        let position = params.text_document_position;
+        let contract = self.get_document(&position.text_document.uri).unwrap_or_default();
+        let items = completion_items(&contract, &position.position);
+        Ok(Some(CompletionResponse::from(items)))
+    }
 
-        let native_variables: Vec<CompletionItem> = NativeVariables::ALL
-            .iter()
-            .map(|var| {
-                let api = make_keyword_reference(&var);
-                CompletionItem {
-                    label: api.name.to_string(),
-                    kind: Some(CompletionItemKind::Field),
-                    detail: Some(api.name.to_string()),
-                    documentation: Some(Documentation::MarkupContent(MarkupContent {
-                        kind: MarkupKind::Markdown,
-                        value: api.description.to_string(),
-                    })),
-                    deprecated: None,
-                    preselect: None,
-                    sort_text: None,
-                    filter_text: None,
-                    insert_text: Some(api.snippet.to_string()),
-                    insert_text_format: Some(InsertTextFormat::PlainText),
-                    text_edit: None,
-                    additional_text_edits: None,
-                    command: None,
-                    data: None,
-                    tags: None,
-                }})
-            .collect();
+    async fn hover(&self, params: TextDocumentPositionParams) -> Result<Option<Hover>> {
+        let contract = match self.get_document(&params.text_document.uri) {
+            Some(contract) => contract,
+            None => return Ok(None)
+        };
+        let state = ContractState::new(&contract);
+        Ok(hover_at(&state, &params.position))
+    }
 
-        let block_properties: Vec<CompletionItem> = BlockInfoProperty::ALL_NAMES
-            .to_vec()
-            .iter()
-            .map(|func| {
-                CompletionItem::new_simple(func.to_string(), "".to_string())})
-            .collect();
+    async fn signature_help(&self, params: TextDocumentPositionParams) -> Result<Option<SignatureHelp>> {
+        let uri = params.text_document.uri;
+        let contract = match self.get_document(&uri) {
+            Some(contract) => contract,
+            None => return Ok(None)
+        };
+        let resolve_contract = |name: &str| {
+            let other_uri = self.workspace_contracts().into_iter()
+                .find(|other_uri| other_uri != &uri && workspace::contract_name(other_uri).as_deref() == Some(name))?;
+            self.read_contract(&other_uri).map(|source| ContractState::new(&source))
+        };
+        Ok(signature_help(&contract, &params.position, &resolve_contract))
+    }
 
-        let items = vec![
-                native_functions, 
-                define_functions, 
-                native_variables, 
-                block_properties]
-            .into_iter()
-            .flatten()
-            .collect::<Vec<CompletionItem>>();
+    async fn goto_definition(&self, params: TextDocumentPositionParams) -> Result<Option<GotoDefinitionResponse>> {
+        let uri = params.text_document.uri;
+        let contract = match self.get_document(&uri) {
+            Some(contract) => contract,
+            None => return Ok(None)
+        };
+        let state = ContractState::new(&contract);
+        let index = SymbolIndex::new(&state.contract_ast);
+        let location = index.definition_at(&params.position).map(|definition| {
+            Location::new(uri, span_to_range(&index.definitions[definition].span))
+        });
+        Ok(location.map(GotoDefinitionResponse::Scalar))
+    }
 
-        let result = CompletionResponse::from(items);
-        Ok(Some(result))
+    async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
+        let uri = params.text_document_position.text_document.uri;
+        let contract = match self.get_document(&uri) {
+            Some(contract) => contract,
+            None => return Ok(None)
+        };
+        let state = ContractState::new(&contract);
+        let index = SymbolIndex::new(&state.contract_ast);
+        let definition = match index.definition_at(&params.text_document_position.position) {
+            Some(definition) => definition,
+            None => return Ok(None)
+        };
+
+        let mut locations = vec![];
+        if params.context.include_declaration {
+            locations.push(Location::new(uri.clone(), span_to_range(&index.definitions[definition].name_span)));
+        }
+        for reference in index.references_to(definition) {
+            locations.push(Location::new(uri.clone(), span_to_range(&reference.span)));
+        }
+        Ok(Some(locations))
     }
 
-    async fn did_open(&self, client: &Client, _: DidOpenTextDocumentParams) {}
+    async fn document_highlight(&self, params: TextDocumentPositionParams) -> Result<Option<Vec<DocumentHighlight>>> {
+        let contract = match self.get_document(&params.text_document.uri) {
+            Some(contract) => contract,
+            None => return Ok(None)
+        };
+        let state = ContractState::new(&contract);
+        let index = SymbolIndex::new(&state.contract_ast);
+        let definition = match index.definition_at(&params.position) {
+            Some(definition) => definition,
+            None => return Ok(None)
+        };
 
-    async fn did_change(&self, client: &Client, _: DidChangeTextDocumentParams) {}
+        let mut highlights = vec![DocumentHighlight {
+            range: span_to_range(&index.definitions[definition].name_span),
+            kind: Some(DocumentHighlightKind::Write),
+        }];
+        for reference in index.references_to(definition) {
+            highlights.push(DocumentHighlight {
+                range: span_to_range(&reference.span),
+                kind: Some(DocumentHighlightKind::Read),
+            });
+        }
+        Ok(Some(highlights))
+    }
 
-    async fn did_save(&self, client: &Client, params: DidSaveTextDocumentParams) {
-        
-        let uri = format!("{:?}", params.text_document.uri);
-        let file_path = params.text_document.uri.to_file_path()
-            .expect("Unable to locate file");
-
-        let contract = fs::read_to_string(file_path)
-            .expect("Unable to read file");
-        
-        let contract_identifier = QualifiedContractIdentifier::transient();
-        let mut contract_ast = match ast::build_ast(&contract_identifier, &contract, &mut ()) {
-            Ok(res) => res,
-            Err(parse_error) => {
-                let range = match parse_error.diagnostic.spans.len() {
-                    0 => Range::default(),
-                    _ => Range {
-                        start: Position {
-                            line: parse_error.diagnostic.spans[0].start_line as u64 - 1,
-                            character: parse_error.diagnostic.spans[0].start_column as u64,
-                        },
-                        end: Position {
-                            line: parse_error.diagnostic.spans[0].end_line as u64 - 1,
-                            character: parse_error.diagnostic.spans[0].end_column as u64,
-                        },
-                    }
-                };
-                let diag = Diagnostic {
-                    /// The range at which the message applies.
-                    range,
+    async fn document_symbol(&self, params: DocumentSymbolParams) -> Result<Option<DocumentSymbolResponse>> {
+        let contract = match self.get_document(&params.text_document.uri) {
+            Some(contract) => contract,
+            None => return Ok(None)
+        };
+        let state = ContractState::new(&contract);
+        Ok(Some(DocumentSymbolResponse::Nested(document_symbols(&state))))
+    }
 
-                    /// The diagnostic's severity. Can be omitted. If omitted it is up to the
-                    /// client to interpret diagnostics as error, warning, info or hint.
-                    severity: Some(DiagnosticSeverity::Error),
+    async fn symbol(&self, params: WorkspaceSymbolParams) -> Result<Option<Vec<SymbolInformation>>> {
+        let symbols = self.workspace_symbols.lock().unwrap().search(&params.query);
+        Ok(Some(symbols))
+    }
 
-                    /// The diagnostic's code. Can be omitted.
-                    code: None,
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        let uri = params.text_document.uri;
+        let contract = match self.get_document(&uri) {
+            Some(contract) => contract,
+            None => return Ok(None)
+        };
+        let state = ContractState::new(&contract);
+        // check errors are not reported on contracts with parse errors.
+        if !state.parse_errors.is_empty() {
+            return Ok(None);
+        }
 
-                    /// A human-readable string describing the source of this
-                    /// diagnostic, e.g. 'typescript' or 'super lint'.
-                    source: Some("clarity".to_string()),
+        let requested = params.range;
+        let is_requested = |diagnostic: &ClarityDiagnostic| {
+            if state.suppressions.is_suppressed(diagnostic) {
+                return false;
+            }
+            match diagnostic.spans.first() {
+                Some(span) => {
+                    let range = span_to_range(span);
+                    range.start <= requested.end && requested.start <= range.end
+                },
+                None => false
+            }
+        };
+        let errors = state.check_errors.iter()
+            .filter(|error| is_requested(&error.diagnostic))
+            .map(|error| (&error.diagnostic, quick_fixes(&state, error)));
+        let warnings = state.check_warnings.iter()
+            .filter(|warning| is_requested(&warning.diagnostic))
+            .map(|warning| (&warning.diagnostic, warning_fixes(&contract, warning)));
+
+        let mut actions = vec![];
+        for (diagnostic, fixes) in errors.chain(warnings) {
+            for fix in fixes {
+                // errors are reported on an expression and on the expressions enclosing it.
+                let is_offered = actions.iter().any(|action| match action {
+                    CodeActionOrCommand::CodeAction(action) => action.title == fix.title,
+                    CodeActionOrCommand::Command(_) => false,
+                });
+                if is_offered {
+                    continue;
+                }
+                let mut changes = HashMap::new();
+                changes.insert(uri.clone(), fix.edits);
+                actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                    title: fix.title,
+                    kind: Some(code_action_kind::QUICKFIX.to_string()),
+                    diagnostics: Some(vec![make_diagnostic(&uri, diagnostic.clone())]),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some(changes),
+                        document_changes: None,
+                    }),
+                    command: None,
+                    is_preferred: None,
+                }));
+            }
+        }
+        Ok(Some(actions))
+    }
 
-                    /// The diagnostic's message.
-                    message: parse_error.diagnostic.message,
+    async fn formatting(&self, params: DocumentFormattingParams) -> Result<Option<Vec<TextEdit>>> {
+        let contract = match self.get_document(&params.text_document.uri) {
+            Some(contract) => contract,
+            None => return Ok(None)
+        };
+        let formatted = match formatter::format_source(&contract) {
+            Some(formatted) => formatted,
+            None => return Ok(None)
+        };
+        if formatted == contract {
+            return Ok(Some(vec![]));
+        }
+        let document = TextDocument::new(contract);
+        let range = Range::new(Position::new(0, 0), document.position_at(document.text().len()));
+        Ok(Some(vec![TextEdit::new(range, formatted)]))
+    }
 
-                    /// An array of related diagnostic information, e.g. when symbol-names within
-                    /// a scope collide all definitions can be marked via this property.
-                    related_information: None,
+    async fn range_formatting(&self, params: DocumentRangeFormattingParams) -> Result<Option<Vec<TextEdit>>> {
+        let contract = match self.get_document(&params.text_document.uri) {
+            Some(contract) => contract,
+            None => return Ok(None)
+        };
+        // whole top-level expressions on the lines of the range are formatted.
+        let Range { start, end } = params.range;
+        let last_line = if end.character == 0 && end.line > start.line { end.line } else { end.line + 1 };
+        let (start_offset, end_offset, formatted) = match formatter::format_lines(&contract, start.line as u32 + 1, last_line as u32) {
+            Some(formatted) => formatted,
+            None => return Ok(None)
+        };
+        if formatted == contract[start_offset..end_offset] {
+            return Ok(Some(vec![]));
+        }
+        let document = TextDocument::new(contract);
+        let range = Range::new(document.position_at(start_offset), document.position_at(end_offset));
+        Ok(Some(vec![TextEdit::new(range, formatted)]))
+    }
 
-                    /// Additional metadata about the diagnostic.
-                    tags: None,
-                }; 
-                client.publish_diagnostics(params.text_document.uri, vec![diag], None);
-                return
-            }
+    async fn prepare_rename(&self, params: TextDocumentPositionParams) -> Result<Option<PrepareRenameResponse>> {
+        let contract = match self.get_document(&params.text_document.uri) {
+            Some(contract) => contract,
+            None => return Ok(None)
         };
+        let state = ContractState::new(&contract);
+        let index = SymbolIndex::new(&state.contract_ast);
+        let range = index.occurrence_at(&params.position)
+            .map(|(_, span)| PrepareRenameResponse::Range(span_to_range(span)));
+        Ok(range)
+    }
 
-        let mut db = AnalysisDatabase::new();
-        let result = analysis::run_analysis(
-            &contract_identifier, 
-            &mut contract_ast.expressions,
-            &mut db, 
-            false,
-            LimitedCostTracker::new_max_limit());
-    
-        let raw_output = format!("{:?}", result);
-
-        let diags = match result {
-            Ok(_) => vec![],
-            Err((check_error, cost_tracker)) => {
-                let range = match check_error.diagnostic.spans.len() {
-                    0 => Range::default(),
-                    _ => Range {
-                        start: Position {
-                            line: check_error.diagnostic.spans[0].start_line as u64 - 1,
-                            character: check_error.diagnostic.spans[0].start_column as u64,
-                        },
-                        end: Position {
-                            line: check_error.diagnostic.spans[0].end_line as u64 - 1,
-                            character: check_error.diagnostic.spans[0].end_column as u64,
-                        },
-                    }
+    async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+        let uri = params.text_document_position.text_document.uri;
+        let contract = match self.get_document(&uri) {
+            Some(contract) => contract,
+            None => return Ok(None)
+        };
+        let state = ContractState::new(&contract);
+        let index = SymbolIndex::new(&state.contract_ast);
+        let definition = match index.definition_at(&params.text_document_position.position) {
+            Some(definition) => definition,
+            None => return Ok(None)
+        };
+        let new_name = rename::validate_new_name(&index, definition, &params.new_name)
+            .map_err(Error::invalid_params)?;
+
+        let mut changes = HashMap::new();
+        changes.insert(uri.clone(), rename::rename_edits(&index, definition, &new_name));
+
+        // functions callable from other contracts are renamed at their call sites too.
+        let renamed = &index.definitions[definition];
+        let is_callable = renamed.kind == DefinitionKind::PublicFunction
+            || renamed.kind == DefinitionKind::ReadOnlyFunction;
+        if let (true, Some(contract_name)) = (is_callable, workspace::contract_name(&uri)) {
+            for other_uri in self.workspace_contracts().into_iter().filter(|other_uri| other_uri != &uri) {
+                let source = match self.read_contract(&other_uri) {
+                    Some(source) => source,
+                    None => continue
                 };
-                let diag = Diagnostic {
-                    range,
-                    severity: Some(DiagnosticSeverity::Error),
-                    code: None,
-                    source: Some("clarity".to_string()),
-                    message: check_error.diagnostic.message,
-                    related_information: None,
-                    tags: None,
-                }; 
-                vec![diag]
-            },
-        };        
+                let (pre_expressions, _) = parser::parse_with_recovery(&source);
+                let edits: Vec<_> = rename::contract_call_sites(&pre_expressions, &contract_name, &renamed.name)
+                    .iter()
+                    .map(|span| TextEdit::new(span_to_range(span), new_name.to_string()))
+                    .collect();
+                if !edits.is_empty() {
+                    changes.insert(other_uri, edits);
+                }
+            }
+        }
 
-        client.publish_diagnostics(params.text_document.uri, diags, None);
+        Ok(Some(WorkspaceEdit {
+            changes: Some(changes),
+            document_changes: None,
+        }))
     }
 
-    async fn did_close(&self, client: &Client, _: DidCloseTextDocumentParams) {}
+    async fn did_open(&self, client: &Client, params: DidOpenTextDocumentParams) {
+        let uri = params.text_document.uri;
+        self.open_document(&uri, params.text_document.text);
+        self.publish_diagnostics(client, uri);
+    }
 
-    // fn symbol(&self, params: WorkspaceSymbolParams) -> Self::SymbolFuture {
-    //     Box::new(future::ok(None))
-    // }
+    async fn did_change(&self, client: &Client, params: DidChangeTextDocumentParams) {
+        let uri = params.text_document.uri;
+        // Changes must be applied before awaiting anything, so that notifications
+        // are processed in the order they were received.
+        self.update_document(&uri, &params.content_changes);
+        self.publish_diagnostics_debounced(client, uri).await;
+    }
 
-    // fn goto_declaration(&self, _: TextDocumentPositionParams) -> Self::DeclarationFuture {
-    //     Box::new(future::ok(None))
-    // }
+    async fn did_save(&self, client: &Client, params: DidSaveTextDocumentParams) {
+        self.publish_diagnostics(client, params.text_document.uri);
+    }
 
-    // fn goto_definition(&self, _: TextDocumentPositionParams) -> Self::DefinitionFuture {
-    //     Box::new(future::ok(None))
-    // }
+    async fn did_close(&self, client: &Client, params: DidCloseTextDocumentParams) {
+        let uri = params.text_document.uri;
+        self.remove_document(&uri);
+        // the unsaved edits of the buffer are gone, the file is indexed as it is on disk.
+        self.index_contract(&uri);
+        client.publish_diagnostics(uri, vec![], None);
+    }
 
-    // fn goto_type_definition(&self, _: TextDocumentPositionParams) -> Self::TypeDefinitionFuture {
-    //     Box::new(future::ok(None))
-    // }
+    async fn did_change_watched_files(&self, client: &Client, params: DidChangeWatchedFilesParams) {
+        for change in params.changes.into_iter() {
+            match change.typ {
+                FileChangeType::Deleted => self.workspace_symbols.lock().unwrap().remove(&change.uri),
+                _ => self.index_contract(&change.uri)
+            }
+        }
+    }
 
-    // fn hover(&self, _: TextDocumentPositionParams) -> Self::HoverFuture {
-    //     // todo(ludo): to implement
-    //     let result = Hover {
-    //         contents: HoverContents::Scalar(MarkedString::String("".to_string())),
-    //         range: None,
-    //     };
+    // fn goto_declaration(&self, _: TextDocumentPositionParams) -> Self::DeclarationFuture {
     //     Box::new(future::ok(None))
     // }
 
-    // fn document_highlight(&self, _: TextDocumentPositionParams) -> Self::HighlightFuture {
+    // fn goto_type_definition(&self, _: TextDocumentPositionParams) -> Self::TypeDefinitionFuture {
     //     Box::new(future::ok(None))
     // }
 }
\ No newline at end of file
@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::*;
+
+use super::clarity::docs::{
+    make_api_reference,
+    make_define_reference,
+    make_keyword_reference};
+use super::clarity::functions::{
+    NativeFunctions,
+    DefineFunctions,
+    NativeVariables,
+    BlockInfoProperty};
+use super::call_context::open_lists;
+use super::contract_state::ContractState;
+use super::document_symbols::document_symbols;
+use super::spans::span_contains;
+use super::symbol_index::{SymbolIndex, DefinitionKind};
+use super::text_document::TextDocument;
+
+/// Completion items at the position: natives, define forms, keywords, and the
+/// definitions of the contract, with their doc comments, along with the local bindings
+/// in scope. When the position is the first argument of a function expecting a data
+/// var, a map, a token or a block property, only those are offered.
+pub fn completion_items(contract: &str, position: &Position) -> Vec<CompletionItem> {
+    let offset = TextDocument::new(contract.to_string()).offset_at(*position);
+    let state = ContractState::new(contract);
+    let index = SymbolIndex::new(&state.contract_ast);
+
+    let innermost_list = open_lists(&contract[..offset]).pop();
+    let first_argument_of = innermost_list.as_ref()
+        .filter(|list| list.active_argument() == Some(0))
+        .and_then(|list| list.function());
+    let expected_kind = match first_argument_of {
+        Some(function) => match NativeFunctions::lookup_by_name(function) {
+            Some(NativeFunctions::GetBlockInfo) => return block_property_items(),
+            Some(NativeFunctions::FetchVar) | Some(NativeFunctions::SetVar) =>
+                Some(DefinitionKind::DataVar),
+            Some(NativeFunctions::FetchEntry) | Some(NativeFunctions::SetEntry) |
+            Some(NativeFunctions::InsertEntry) | Some(NativeFunctions::DeleteEntry) =>
+                Some(DefinitionKind::Map),
+            Some(NativeFunctions::GetTokenBalance) | Some(NativeFunctions::TransferToken) |
+            Some(NativeFunctions::MintToken) =>
+                Some(DefinitionKind::FungibleToken),
+            Some(NativeFunctions::GetAssetOwner) | Some(NativeFunctions::TransferAsset) |
+            Some(NativeFunctions::MintAsset) =>
+                Some(DefinitionKind::NonFungibleToken),
+            _ => None
+        },
+        _ => None
+    };
+
+    let details: HashMap<_, _> = document_symbols(&state).into_iter()
+        .map(|symbol| (symbol.name, symbol.detail))
+        .collect();
+    let definitions = index.definitions.iter()
+        .filter(|definition| match expected_kind {
+            Some(kind) => definition.kind == kind,
+            None => true
+        })
+        .filter(|definition| match definition.scope {
+            Some(ref scope) => span_contains(scope, position) && !span_contains(&definition.name_span, position),
+            None => true
+        })
+        .map(|definition| CompletionItem {
+            label: definition.name.to_string(),
+            kind: Some(completion_kind(definition.kind)),
+            detail: match definition.scope {
+                Some(_) => None,
+                None => details.get(definition.name.as_str()).cloned().flatten()
+            },
+            documentation: match definition.scope {
+                Some(_) => None,
+                None => state.doc_comments.get(definition.name.as_str()).map(|doc| Documentation::MarkupContent(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: doc.to_markdown(),
+                }))
+            },
+            ..CompletionItem::default()
+        });
+
+    if expected_kind.is_some() {
+        return definitions.collect();
+    }
+
+    let mut items: Vec<_> = definitions.collect();
+    items.extend(native_function_items());
+    items.extend(define_function_items());
+    items.extend(native_variable_items());
+    items
+}
+
+fn completion_kind(kind: DefinitionKind) -> CompletionItemKind {
+    match kind {
+        DefinitionKind::PrivateFunction | DefinitionKind::PublicFunction | DefinitionKind::ReadOnlyFunction =>
+            CompletionItemKind::Function,
+        DefinitionKind::Constant => CompletionItemKind::Constant,
+        DefinitionKind::DataVar | DefinitionKind::Parameter | DefinitionKind::LetBinding | DefinitionKind::MatchBinding =>
+            CompletionItemKind::Variable,
+        DefinitionKind::Map => CompletionItemKind::Struct,
+        DefinitionKind::FungibleToken | DefinitionKind::NonFungibleToken => CompletionItemKind::Value,
+        DefinitionKind::Trait | DefinitionKind::UseTrait => CompletionItemKind::Interface,
+    }
+}
+
+fn native_function_items() -> Vec<CompletionItem> {
+    NativeFunctions::ALL
+        .iter()
+        .map(|func| {
+            let api = make_api_reference(func);
+            CompletionItem {
+                label: api.name.to_string(),
+                kind: Some(CompletionItemKind::Function),
+                detail: Some(api.name.to_string()),
+                documentation: Some(Documentation::MarkupContent(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: api.description.to_string(),
+                })),
+                deprecated: None,
+                preselect: None,
+                sort_text: None,
+                filter_text: None,
+                insert_text: Some(api.snippet.clone()),
+                insert_text_format: Some(InsertTextFormat::Snippet),
+                text_edit: None,
+                additional_text_edits: None,
+                command: None,
+                data: None,
+                tags: None,
+            }})
+        .collect()
+}
+
+fn define_function_items() -> Vec<CompletionItem> {
+    DefineFunctions::ALL
+        .iter()
+        .map(|func| {
+            let api = make_define_reference(func);
+            CompletionItem {
+                label: api.name.to_string(),
+                kind: Some(CompletionItemKind::Class),
+                detail: Some(api.name.to_string()),
+                documentation: Some(Documentation::MarkupContent(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: api.description.to_string(),
+                })),
+                deprecated: None,
+                preselect: None,
+                sort_text: None,
+                filter_text: None,
+                insert_text: Some(api.snippet.clone()),
+                insert_text_format: Some(InsertTextFormat::Snippet),
+                text_edit: None,
+                additional_text_edits: None,
+                command: None,
+                data: None,
+                tags: None,
+            }})
+        .collect()
+}
+
+fn native_variable_items() -> Vec<CompletionItem> {
+    NativeVariables::ALL
+        .iter()
+        .map(|var| {
+            let api = make_keyword_reference(var);
+            CompletionItem {
+                label: api.name.to_string(),
+                kind: Some(CompletionItemKind::Field),
+                detail: Some(api.name.to_string()),
+                documentation: Some(Documentation::MarkupContent(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: api.description.to_string(),
+                })),
+                deprecated: None,
+                preselect: None,
+                sort_text: None,
+                filter_text: None,
+                insert_text: Some(api.snippet.to_string()),
+                insert_text_format: Some(InsertTextFormat::PlainText),
+                text_edit: None,
+                additional_text_edits: None,
+                command: None,
+                data: None,
+                tags: None,
+            }})
+        .collect()
+}
+
+fn block_property_items() -> Vec<CompletionItem> {
+    BlockInfoProperty::ALL_NAMES
+        .to_vec()
+        .iter()
+        .map(|func| {
+            CompletionItem::new_simple(func.to_string(), "".to_string())})
+        .collect()
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    /// Labels offered at the end of the contract.
+    fn labels_at_end(contract: &str) -> Vec<String> {
+        let line = contract.matches('\n').count();
+        let character = contract.len() - contract.rfind('\n').map(|ix| ix + 1).unwrap_or(0);
+        completion_items(contract, &Position::new(line as u64, character as u64)).into_iter()
+            .map(|item| item.label)
+            .collect()
+    }
+
+    #[test]
+    fn test_completion_is_filtered_by_context() {
+        let definitions = "(define-data-var counter uint u0)\n\
+                           (define-data-var owner principal tx-sender)\n\
+                           (define-map balances principal uint)\n\
+                           (define-fungible-token gold)\n\
+                           (define-non-fungible-token badge uint)\n";
+        assert_eq!(labels_at_end(&format!("{}(define-read-only (get) (var-get ", definitions)), vec!["counter", "owner"]);
+        assert_eq!(labels_at_end(&format!("{}(define-read-only (get) (map-get? ", definitions)), vec!["balances"]);
+        assert_eq!(labels_at_end(&format!("{}(define-public (mint) (ft-mint? ", definitions)), vec!["gold"]);
+        assert_eq!(labels_at_end(&format!("{}(define-public (mint) (nft-mint? ", definitions)), vec!["badge"]);
+        let properties = labels_at_end("(get-block-info? ");
+        assert_eq!(properties.len(), BlockInfoProperty::ALL_NAMES.len());
+        assert!(!labels_at_end("(get-block-info? time ").contains(&"time".to_string()));
+    }
+
+    #[test]
+    fn test_completion_offers_bindings_in_scope() {
+        let contract = "(define-constant limit u10)\n\
+                        (define-private (f (a uint)) (let ((b u1)) (+ a b )))\n\
+                        (define-private (g (c uint)) c)";
+        let items = completion_items(contract, &Position::new(1, 49));
+        let labels: Vec<_> = items.iter().map(|item| item.label.as_str()).collect();
+        assert_eq!(labels[..5], ["limit", "f", "g", "a", "b"]);
+        assert!(!labels.contains(&"c"));
+        assert!(labels.contains(&"var-get") && labels.contains(&"define-map") && labels.contains(&"tx-sender"));
+        assert_eq!(items[1].detail, Some("private (f (a uint)) -> uint".to_string()));
+    }
+
+    #[test]
+    fn test_completion_documents_definitions() {
+        let contract = ";; Upper bound.\n(define-data-var limit uint u10)\n(define-private (f (a uint)) (+ a ))";
+        let items = completion_items(contract, &Position::new(2, 34));
+        assert_eq!(items[0].documentation, Some(Documentation::MarkupContent(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: "Upper bound.".to_string(),
+        })));
+        assert_eq!(items[1].documentation, None);
+    }
+}
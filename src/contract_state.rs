@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+use super::clarity::analysis::{self, AnalysisDatabase, ContractAnalysis, CheckError, CheckWarning};
+use super::clarity::analysis::errors::CheckErrors;
+use super::clarity::ast::{self, ContractAST};
+use super::clarity::ast::errors::ParseError;
+use super::clarity::costs::LimitedCostTracker;
+use super::clarity::types::QualifiedContractIdentifier;
+use super::doc_comments::{doc_comments, DocComment};
+use super::spans::spans_overlap;
+use super::suppressions::Suppressions;
+use super::symbol_index::SymbolIndex;
+
+/// What the server knows about a contract buffer, once built and checked.
+/// Contracts with parse errors are still analysed, as far as the recovered AST allows,
+/// so that editor features keep working while a contract is being written.
+pub struct ContractState {
+    pub contract_ast: ContractAST,
+    pub analysis: ContractAnalysis,
+    pub parse_errors: Vec<ParseError>,
+    pub check_errors: Vec<CheckError>,
+    /// Findings of the lint passes.
+    pub check_warnings: Vec<CheckWarning>,
+    /// Documentation of the definitions, by name.
+    pub doc_comments: HashMap<String, DocComment>,
+    /// Diagnostic codes allowed by `clarity-lsp: allow(...)` comments.
+    pub suppressions: Suppressions,
+}
+
+impl ContractState {
+
+    pub fn new(contract: &str) -> ContractState {
+        let contract_identifier = QualifiedContractIdentifier::transient();
+        let (mut contract_ast, parse_errors) = ast::build_ast_with_recovery(&contract_identifier, contract, &mut ());
+
+        let mut db = AnalysisDatabase::new();
+        let (analysis, mut check_errors) = analysis::run_analysis_collecting_errors(
+            &contract_identifier,
+            &mut contract_ast.expressions,
+            &mut db,
+            false,
+            LimitedCostTracker::new_max_limit());
+        relate_name_conflicts(&contract_ast, &mut check_errors);
+        let check_warnings = analysis::run_lints(&analysis);
+
+        ContractState {
+            contract_ast,
+            analysis,
+            parse_errors,
+            check_errors,
+            check_warnings,
+            doc_comments: doc_comments(contract),
+            suppressions: Suppressions::new(contract),
+        }
+    }
+}
+
+/// Points the errors about names already in use at the definition using the name. The
+/// checker only knows names, while the symbol index knows where they are defined.
+fn relate_name_conflicts(contract_ast: &ContractAST, check_errors: &mut [CheckError]) {
+    let index = SymbolIndex::new(contract_ast);
+    for error in check_errors.iter_mut() {
+        let name = match error.err {
+            CheckErrors::NameAlreadyUsed(ref name) => name,
+            _ => continue
+        };
+        let span = match error.diagnostic.spans.first() {
+            Some(span) => span.clone(),
+            None => continue
+        };
+        let definition = index.definitions.iter()
+            .find(|definition| definition.name.as_str() == name && !spans_overlap(&definition.name_span, &span));
+        if let Some(definition) = definition {
+            error.diagnostic.add_related_information(definition.name_span.clone(), format!("'{}' is defined here", name));
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use super::super::clarity::diagnostic::RelatedInformation;
+    use super::super::clarity::representations::Span;
+
+    fn related_information(contract: &str) -> Vec<RelatedInformation> {
+        let state = ContractState::new(contract);
+        state.check_errors[0].diagnostic.related_information.clone()
+    }
+
+    fn span(line: u32, start_column: u32, end_column: u32) -> Span {
+        Span { start_line: line, start_column, end_line: line, end_column }
+    }
+
+    #[test]
+    fn test_errors_point_at_related_expressions() {
+        assert_eq!(related_information("(define-constant limit u1)\n(define-read-only (f) (let ((limit u2)) limit))"), vec![
+            RelatedInformation { span: span(1, 18, 22), message: "'limit' is defined here".to_string() },
+        ]);
+        assert_eq!(related_information("(define-read-only (f) (if true u1 1))"), vec![
+            RelatedInformation { span: span(1, 32, 33), message: "this branch is of type 'uint'".to_string() },
+            RelatedInformation { span: span(1, 35, 35), message: "this branch is of type 'int'".to_string() },
+        ]);
+    }
+}
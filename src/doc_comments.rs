@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+
+use super::clarity::ast::cst::{self, CstNode, Trivia};
+use super::clarity::functions::DefineFunctions;
+
+/// Documentation written in the `;;` comment lines right above a definition, e.g.
+///
+/// ```clarity
+/// ;; Moves tokens to the recipient.
+/// ;; @param amount number of tokens to move
+/// ;; @returns true once transferred
+/// (define-public (transfer (amount uint) (recipient principal)) ...)
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DocComment {
+    pub description: String,
+    /// Descriptions of the parameters, by name, in the order they are documented.
+    pub params: Vec<(String, String)>,
+    pub returns: Option<String>,
+}
+
+impl DocComment {
+
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params.iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, description)| description.as_str())
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut sections = vec![];
+        if !self.description.is_empty() {
+            sections.push(self.description.clone());
+        }
+        if !self.params.is_empty() {
+            let params: Vec<_> = self.params.iter()
+                .map(|(name, description)| format!("- `{}` {}", name, description))
+                .collect();
+            sections.push(format!("**Parameters**\n{}", params.join("\n")));
+        }
+        if let Some(ref returns) = self.returns {
+            sections.push(format!("**Returns** {}", returns));
+        }
+        sections.join("\n\n")
+    }
+}
+
+/// Doc comments of the functions, maps and data vars of the contract, by name.
+pub fn doc_comments(contract: &str) -> HashMap<String, DocComment> {
+    let lines: Vec<_> = contract.lines().collect();
+    let mut comments = HashMap::new();
+    for node in cst::parse(contract).nodes.iter() {
+        let name = match documented_name(node) {
+            Some(name) => name,
+            None => continue
+        };
+        let block = comment_block(node, &lines);
+        if !block.is_empty() {
+            comments.insert(name.to_string(), parse_doc_comment(&block));
+        }
+    }
+    comments
+}
+
+/// Name of the definition, for the definitions carrying documentation.
+fn documented_name(node: &CstNode) -> Option<&str> {
+    fn atom(node: &CstNode) -> Option<&str> {
+        match node {
+            CstNode::Atom(token, _) => Some(token.text.as_str()),
+            _ => None
+        }
+    }
+    let children = node.children();
+    match DefineFunctions::lookup_by_name(atom(children.first()?)?)? {
+        DefineFunctions::PublicFunction | DefineFunctions::ReadOnlyFunction | DefineFunctions::PrivateFunction =>
+            atom(children.get(1)?.children().first()?),
+        DefineFunctions::Map | DefineFunctions::PersistedVariable =>
+            atom(children.get(1)?),
+        _ => None
+    }
+}
+
+/// The comments on the lines right above the node, each on a line of its own.
+fn comment_block<'a>(node: &'a CstNode, lines: &[&str]) -> Vec<&'a str> {
+    let mut block = vec![];
+    let mut expected_line = node.span().start_line - 1;
+    for trivia in node.first_token().leading_trivia.iter().rev() {
+        let (comment, span) = match trivia {
+            Trivia::Comment(comment, span) => (comment, span),
+            Trivia::Whitespace(_) => continue,
+        };
+        let starts_line = lines.get(span.start_line as usize - 1)
+            .and_then(|line| line.get(..span.start_column as usize - 1))
+            .map(|before| before.trim().is_empty())
+            .unwrap_or(false);
+        if span.start_line != expected_line || !starts_line {
+            break;
+        }
+        block.push(comment.as_str());
+        expected_line -= 1;
+    }
+    block.reverse();
+    block
+}
+
+fn parse_doc_comment(block: &[&str]) -> DocComment {
+    enum Section { Description, Param, Returns }
+
+    let mut doc = DocComment::default();
+    let mut description = vec![];
+    let mut section = Section::Description;
+    for line in block.iter() {
+        let text = line.trim_start_matches(';').trim();
+        if let Some(param) = text.strip_prefix("@param") {
+            let mut words = param.trim().splitn(2, char::is_whitespace);
+            let name = words.next().unwrap_or_default().to_string();
+            let param_description = words.next().unwrap_or_default().trim().to_string();
+            doc.params.push((name, param_description));
+            section = Section::Param;
+        } else if let Some(returns) = text.strip_prefix("@returns").or_else(|| text.strip_prefix("@return")) {
+            doc.returns = Some(returns.trim().to_string());
+            section = Section::Returns;
+        } else {
+            // lines following an annotation continue it.
+            let continued = match section {
+                Section::Description => {
+                    description.push(text);
+                    continue;
+                },
+                Section::Param => doc.params.last_mut().map(|(_, description)| description),
+                Section::Returns => doc.returns.as_mut(),
+            };
+            if let Some(continued) = continued {
+                if !text.is_empty() {
+                    continued.push(' ');
+                    continued.push_str(text);
+                }
+            }
+        }
+    }
+    doc.description = description.join("\n").trim().to_string();
+    doc
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn test_doc_comments() {
+        let contract = ";; Total supply.\n\
+                        (define-data-var supply uint u0) ;; not documentation\n\
+                        (define-map balances principal uint)\n\
+                        \n\
+                        ;; Moves tokens to the recipient.\n\
+                        ;;\n\
+                        ;; Fails when the balance is too low.\n\
+                        ;; @param amount number of tokens\n\
+                        ;;   to move\n\
+                        ;; @param recipient who receives them\n\
+                        ;; @returns true once transferred\n\
+                        (define-public (transfer (amount uint) (recipient principal)) (ok true))\n\
+                        ;; detached\n\
+                        \n\
+                        (define-constant limit u10)\n\
+                        ;; also detached\n\
+                        \n\
+                        (define-private (f) true)";
+        let comments = doc_comments(contract);
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments["supply"].description, "Total supply.");
+
+        let transfer = &comments["transfer"];
+        assert_eq!(transfer.description, "Moves tokens to the recipient.\n\nFails when the balance is too low.");
+        assert_eq!(transfer.param("amount"), Some("number of tokens to move"));
+        assert_eq!(transfer.param("recipient"), Some("who receives them"));
+        assert_eq!(transfer.returns, Some("true once transferred".to_string()));
+        assert_eq!(transfer.to_markdown(),
+                   "Moves tokens to the recipient.\n\nFails when the balance is too low.\n\n\
+                    **Parameters**\n- `amount` number of tokens to move\n- `recipient` who receives them\n\n\
+                    **Returns** true once transferred");
+    }
+}
@@ -0,0 +1,223 @@
+use tower_lsp::lsp_types::{DocumentSymbol, SymbolKind};
+
+use super::clarity::analysis::ContractAnalysis;
+use super::clarity::functions::DefineFunctions;
+use super::clarity::representations::{PreSymbolicExpression, PreSymbolicExpressionType};
+use super::clarity::types::{FunctionType, Value};
+use super::contract_state::ContractState;
+use super::spans::span_to_range;
+
+/// Outlines the top-level definitions of a contract. The outline is read from the
+/// pre-expressions, so that it is available even when the analysis fails: the types
+/// inferred by the analysis only refine the details when they are known.
+pub fn document_symbols(state: &ContractState) -> Vec<DocumentSymbol> {
+    state.contract_ast.pre_expressions.iter()
+        .filter_map(|expr| definition_symbol(expr, &state.analysis))
+        .collect()
+}
+
+fn definition_symbol(expr: &PreSymbolicExpression, analysis: &ContractAnalysis) -> Option<DocumentSymbol> {
+    let list = expr.match_list()?;
+    let define_type = DefineFunctions::lookup_by_name(list.first()?.match_atom()?)?;
+    let args = &list[1..];
+
+    let (name_expr, kind, detail, children) = match define_type {
+        DefineFunctions::PrivateFunction | DefineFunctions::PublicFunction | DefineFunctions::ReadOnlyFunction => {
+            let (visibility, function_type) = match define_type {
+                DefineFunctions::PrivateFunction => ("private", &analysis.private_function_types),
+                DefineFunctions::PublicFunction => ("public", &analysis.public_function_types),
+                _ => ("read-only", &analysis.read_only_function_types),
+            };
+            let signature = args.first()?.match_list()?;
+            let name_expr = signature.first()?;
+            let mut items = vec![name_expr.match_atom()?.to_string()];
+            let detail = match function_type.get(name_expr.match_atom()?) {
+                Some(FunctionType::Fixed(function)) => {
+                    items.extend(function.args.iter().map(|arg| format!("({} {})", arg.name.as_str(), arg.signature)));
+                    join_with_returns(items, Some(&function.returns))
+                },
+                _ => {
+                    items.extend(signature[1..].iter().map(render));
+                    join_with_returns(items, None::<&str>)
+                }
+            };
+            let detail = format!("{} {}", visibility, detail);
+            (name_expr, SymbolKind::Function, Some(detail), None)
+        },
+        DefineFunctions::Constant => {
+            let name_expr = args.first()?;
+            let detail = analysis.variable_types.get(name_expr.match_atom()?)
+                .map(|type_signature| type_signature.to_string())
+                .or_else(|| args.get(1).map(render));
+            (name_expr, SymbolKind::Constant, detail, None)
+        },
+        DefineFunctions::PersistedVariable => {
+            let name_expr = args.first()?;
+            let detail = analysis.persisted_variable_types.get(name_expr.match_atom()?)
+                .map(|type_signature| type_signature.to_string())
+                .or_else(|| args.get(1).map(render));
+            (name_expr, SymbolKind::Variable, detail, None)
+        },
+        DefineFunctions::Map => {
+            let name_expr = args.first()?;
+            let detail = analysis.map_types.get(name_expr.match_atom()?)
+                .map(|(key_type, value_type)| format!("{} -> {}", key_type, value_type))
+                .or_else(|| match (args.get(1), args.get(2)) {
+                    (Some(key_type), Some(value_type)) => Some(format!("{} -> {}", render(key_type), render(value_type))),
+                    _ => None
+                });
+            (name_expr, SymbolKind::Struct, detail, None)
+        },
+        DefineFunctions::FungibleToken => {
+            let name_expr = args.first()?;
+            let detail = args.get(1).map(|max_supply| format!("max supply {}", render(max_supply)));
+            (name_expr, SymbolKind::Object, detail, None)
+        },
+        DefineFunctions::NonFungibleToken => {
+            let name_expr = args.first()?;
+            let detail = analysis.non_fungible_tokens.get(name_expr.match_atom()?)
+                .map(|asset_type| asset_type.to_string())
+                .or_else(|| args.get(1).map(render));
+            (name_expr, SymbolKind::Object, detail, None)
+        },
+        DefineFunctions::Trait => {
+            let name_expr = args.first()?;
+            let trait_functions = analysis.defined_traits.get(name_expr.match_atom()?);
+            let children = args.get(1).and_then(|functions| functions.match_list()).map(|functions| {
+                functions.iter().filter_map(|function| {
+                    let signature = function.match_list()?;
+                    let function_name = signature.first()?;
+                    let detail = match trait_functions.and_then(|functions| functions.get(function_name.match_atom()?)) {
+                        Some(function_type) => {
+                            let args = function_type.args.iter().map(|arg| arg.to_string()).collect();
+                            join_with_returns(args, Some(&function_type.returns))
+                        },
+                        None => {
+                            let args = signature.get(1).and_then(|args| args.match_list())
+                                .map(|args| args.iter().map(render).collect())
+                                .unwrap_or_else(Vec::new);
+                            join_with_returns(args, signature.get(2).map(render).as_ref())
+                        }
+                    };
+                    Some(make_symbol(function_name.match_atom()?.to_string(), SymbolKind::Method, Some(detail), function, function_name, None))
+                }).collect()
+            });
+            (name_expr, SymbolKind::Interface, None, children)
+        },
+        DefineFunctions::UseTrait => {
+            let name_expr = args.first()?;
+            (name_expr, SymbolKind::Module, args.get(1).map(render), None)
+        },
+        DefineFunctions::ImplTrait => {
+            // implementations are not named, the symbol is named after the implemented trait.
+            let trait_expr = args.first()?;
+            return Some(make_symbol(render(trait_expr), SymbolKind::Module, Some("impl-trait".to_string()), expr, trait_expr, None));
+        },
+    };
+
+    Some(make_symbol(name_expr.match_atom()?.to_string(), kind, detail, expr, name_expr, children))
+}
+
+fn make_symbol(name: String, kind: SymbolKind, detail: Option<String>, expr: &PreSymbolicExpression,
+               name_expr: &PreSymbolicExpression, children: Option<Vec<DocumentSymbol>>) -> DocumentSymbol {
+    DocumentSymbol {
+        name,
+        detail,
+        kind,
+        deprecated: None,
+        range: span_to_range(&expr.span),
+        selection_range: span_to_range(&name_expr.span),
+        children,
+    }
+}
+
+fn join_with_returns<T: ToString>(args: Vec<String>, returns: Option<T>) -> String {
+    let args = format!("({})", args.join(" "));
+    match returns {
+        Some(returns) => format!("{} -> {}", args, returns.to_string()),
+        None => args
+    }
+}
+
+/// Renders a pre-expression back to Clarity source, e.g. a type signature.
+fn render(expr: &PreSymbolicExpression) -> String {
+    match expr.pre_expr {
+        PreSymbolicExpressionType::AtomValue(Value::Principal(ref principal)) => format!("'{}", principal),
+        PreSymbolicExpressionType::AtomValue(ref value) => value.to_string(),
+        PreSymbolicExpressionType::Atom(ref name) => name.to_string(),
+        PreSymbolicExpressionType::List(ref list) => {
+            let items: Vec<_> = list.iter().map(render).collect();
+            format!("({})", items.join(" "))
+        },
+        PreSymbolicExpressionType::Tuple(ref list) => {
+            let entries: Vec<_> = list.chunks(2)
+                .map(|entry| entry.iter().map(render).collect::<Vec<_>>().join(": "))
+                .collect();
+            format!("{{{}}}", entries.join(", "))
+        },
+        PreSymbolicExpressionType::SugaredContractIdentifier(ref contract_name) => format!(".{}", contract_name.as_str()),
+        PreSymbolicExpressionType::SugaredFieldIdentifier(ref contract_name, ref name) => format!(".{}.{}", contract_name.as_str(), name.as_str()),
+        PreSymbolicExpressionType::FieldIdentifier(ref trait_identifier) => format!("'{}", trait_identifier),
+        PreSymbolicExpressionType::TraitReference(ref name) => format!("<{}>", name.as_str()),
+        PreSymbolicExpressionType::Placeholder(ref text) => text.clone(),
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    fn outline(contract: &str) -> Vec<(String, SymbolKind, Option<String>)> {
+        let state = ContractState::new(contract);
+        document_symbols(&state).into_iter()
+            .map(|symbol| (symbol.name, symbol.kind, symbol.detail))
+            .collect()
+    }
+
+    #[test]
+    fn test_document_symbols() {
+        let contract = "(define-trait transferable ((transfer (uint principal) (response bool uint))))\n\
+                        (use-trait token .token-trait.token)\n\
+                        (impl-trait .token-trait.token)\n\
+                        (define-constant owner tx-sender)\n\
+                        (define-data-var counter uint u0)\n\
+                        (define-map balances {owner: principal} {amount: uint})\n\
+                        (define-fungible-token gold u100)\n\
+                        (define-non-fungible-token badge uint)\n\
+                        (define-read-only (get-counter) (var-get counter))\n\
+                        (define-public (add (amount uint)) (if (> amount u0) (ok amount) (err u1)))";
+        let state = ContractState::new(contract);
+        let symbols = document_symbols(&state);
+        assert_eq!(symbols.len(), 10);
+        let trait_functions = symbols[0].children.as_ref().unwrap();
+        assert_eq!(trait_functions[0].name, "transfer");
+        assert_eq!(trait_functions[0].kind, SymbolKind::Method);
+        assert_eq!(trait_functions[0].detail, Some("(uint principal) -> (response bool uint)".to_string()));
+        assert_eq!(symbols[8].selection_range.start.character, 19);
+
+        let outline: Vec<_> = symbols.into_iter().map(|symbol| (symbol.name, symbol.kind, symbol.detail)).collect();
+        assert_eq!(outline[1..], [
+            ("token".to_string(), SymbolKind::Module, Some(".token-trait.token".to_string())),
+            (".token-trait.token".to_string(), SymbolKind::Module, Some("impl-trait".to_string())),
+            ("owner".to_string(), SymbolKind::Constant, Some("principal".to_string())),
+            ("counter".to_string(), SymbolKind::Variable, Some("uint".to_string())),
+            ("balances".to_string(), SymbolKind::Struct, Some("{owner: principal} -> {amount: uint}".to_string())),
+            ("gold".to_string(), SymbolKind::Object, Some("max supply u100".to_string())),
+            ("badge".to_string(), SymbolKind::Object, Some("uint".to_string())),
+            ("get-counter".to_string(), SymbolKind::Function, Some("read-only (get-counter) -> uint".to_string())),
+            ("add".to_string(), SymbolKind::Function, Some("public (add (amount uint)) -> (response uint uint)".to_string())),
+        ]);
+    }
+
+    #[test]
+    fn test_document_symbols_without_analysis() {
+        let contract = "(define-map balances {owner: principal} {amount: uint})\n\
+                        (define-public (add (amount uint)) (ok (+ amount true)))\n\
+                        (define-constant limit";
+        assert_eq!(outline(contract), vec![
+            ("balances".to_string(), SymbolKind::Struct, Some("{owner: principal} -> {amount: uint}".to_string())),
+            ("add".to_string(), SymbolKind::Function, Some("public (add (amount uint))".to_string())),
+            ("limit".to_string(), SymbolKind::Constant, None),
+        ]);
+    }
+}
@@ -0,0 +1,135 @@
+use tower_lsp::lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind, Position};
+
+use super::clarity::docs::{
+    make_api_reference,
+    make_define_reference,
+    make_keyword_reference,
+    FunctionAPI,
+    KeywordAPI};
+use super::clarity::functions::{NativeFunctions, DefineFunctions, NativeVariables};
+use super::clarity::representations::{SymbolicExpression, SymbolicExpressionType};
+use super::contract_state::ContractState;
+use super::document_symbols::document_symbols;
+use super::spans::{span_contains, span_to_range};
+
+/// Describes the expression under the cursor: native functions, keywords and define
+/// forms get their reference documentation, documented definitions their doc comment,
+/// and any other expression its inferred type.
+pub fn hover_at(state: &ContractState, position: &Position) -> Option<Hover> {
+    let expr = find_innermost_expression(&state.contract_ast.expressions, position)?;
+
+    let documentation = match expr.expr {
+        SymbolicExpressionType::Atom(ref name) => {
+            NativeFunctions::lookup_by_name(name)
+                .map(|native_function| format_function_api(&make_api_reference(&native_function)))
+                .or_else(|| DefineFunctions::lookup_by_name(name)
+                    .map(|define_function| format_function_api(&make_define_reference(&define_function))))
+                .or_else(|| NativeVariables::lookup_by_name(name)
+                    .map(|native_variable| format_keyword_api(&make_keyword_reference(&native_variable))))
+                .or_else(|| format_doc_comment(state, name))
+        },
+        _ => None
+    };
+
+    let value = match documentation {
+        Some(documentation) => documentation,
+        None => {
+            let type_signature = state.analysis.type_map.as_ref()?.get_type(expr)?;
+            format!("```clarity\n{}\n```", type_signature)
+        }
+    };
+
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value,
+        }),
+        range: Some(span_to_range(&expr.span)),
+    })
+}
+
+/// Finds the deepest expression whose span contains the position.
+pub fn find_innermost_expression<'a>(expressions: &'a [SymbolicExpression], position: &Position) -> Option<&'a SymbolicExpression> {
+    for expr in expressions.iter() {
+        // lists synthesized by the sugar expander, e.g. tuple entries, have no span
+        // of their own, so children are always searched.
+        if let Some(list) = expr.match_list() {
+            if let Some(inner) = find_innermost_expression(list, position) {
+                return Some(inner);
+            }
+        }
+        if span_contains(&expr.span, position) {
+            return Some(expr);
+        }
+    }
+    None
+}
+
+fn format_function_api(api: &FunctionAPI) -> String {
+    format!("```clarity\n{}\n```\n\n{}\n\n**Example**\n```clarity\n{}\n```",
+            api.signature, api.description, api.example)
+}
+
+/// The doc comment of a definition, under its signature as outlined in the document.
+fn format_doc_comment(state: &ContractState, name: &str) -> Option<String> {
+    let doc = state.doc_comments.get(name)?;
+    let detail = document_symbols(state).into_iter()
+        .find(|symbol| symbol.name == name)
+        .and_then(|symbol| symbol.detail);
+    match detail {
+        Some(detail) => Some(format!("```clarity\n{}\n```\n\n{}", detail, doc.to_markdown())),
+        None => Some(doc.to_markdown()),
+    }
+}
+
+fn format_keyword_api(api: &KeywordAPI) -> String {
+    format!("```clarity\n{}: {}\n```\n\n{}\n\n**Example**\n```clarity\n{}\n```",
+            api.name, api.output_type, api.description, api.example)
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    fn hover_text(contract: &str, line: u64, character: u64) -> Option<String> {
+        let state = ContractState::new(contract);
+        hover_at(&state, &Position::new(line, character)).map(|hover| match hover.contents {
+            HoverContents::Markup(content) => content.value,
+            _ => unreachable!()
+        })
+    }
+
+    #[test]
+    fn test_hover_shows_inferred_types() {
+        let contract = "(define-read-only (double (n uint))\n  (ok (* n u2)))";
+        assert_eq!(hover_text(contract, 1, 9), Some("```clarity\nuint\n```".to_string()));
+        assert_eq!(hover_text(contract, 1, 6), Some("```clarity\nuint\n```".to_string()));
+        assert_eq!(hover_text(contract, 1, 2), Some("```clarity\n(response uint UnknownType)\n```".to_string()));
+        assert_eq!(hover_text(contract, 0, 27), None);
+    }
+
+    #[test]
+    fn test_hover_shows_reference_documentation() {
+        let contract = "(define-read-only (get-sender)\n  (ok tx-sender))\n(define-constant a";
+        let define = hover_text(contract, 0, 3).unwrap();
+        assert!(define.starts_with("```clarity\n(define-read-only (function-name (arg-name-0 arg-type-0)"));
+        let native = hover_text(contract, 1, 4).unwrap();
+        assert!(native.contains("(ok value)"));
+        let keyword = hover_text(contract, 1, 8).unwrap();
+        assert!(keyword.starts_with("```clarity\ntx-sender: principal\n```"));
+        assert_eq!(hover_text(contract, 1, 20), None);
+    }
+
+    #[test]
+    fn test_hover_shows_doc_comments() {
+        let contract = ";; Doubles the number.\n\
+                        ;; @param n any number\n\
+                        (define-read-only (double (n uint)) (ok (* n u2)))\n\
+                        (define-read-only (quadruple (n uint)) (double (* n u2)))";
+        let expected = "```clarity\nread-only (double (n uint)) -> (response uint UnknownType)\n```\n\n\
+                        Doubles the number.\n\n**Parameters**\n- `n` any number";
+        assert_eq!(hover_text(contract, 3, 41), Some(expected.to_string()));
+        assert_eq!(hover_text(contract, 2, 20), Some(expected.to_string()));
+        assert_eq!(hover_text(contract, 3, 21), None);
+    }
+}
@@ -8,7 +8,23 @@
 #[macro_use] extern crate lazy_static;
 
 pub mod clarity;
+mod call_context;
 mod clarity_language_backend;
+mod completion;
+mod contract_state;
+mod doc_comments;
+mod document_symbols;
+mod hover;
+mod quick_fixes;
+mod rename;
+mod semantic_tokens;
+mod signature_help;
+mod spans;
+mod suppressions;
+mod symbol_index;
+mod text_document;
+mod workspace;
+mod workspace_symbols;
 
 use clarity_language_backend::ClarityLanguageBackend;
 use tokio;
@@ -19,7 +35,9 @@ async fn main() {
     let stdin = tokio::io::stdin();
     let stdout = tokio::io::stdout();
 
-    let (service, messages) = LspService::new(ClarityLanguageBackend::default());
+    let backend = ClarityLanguageBackend::default();
+    let extension_methods = backend.extension_methods();
+    let (service, messages) = LspService::with_handler(backend, extension_methods);
     Server::new(stdin, stdout)
         .interleave(messages)
         .serve(service)
@@ -0,0 +1,560 @@
+use tower_lsp::lsp_types::{Position, Range, TextEdit};
+
+use super::clarity::analysis::errors::{CheckError, CheckErrors};
+use super::clarity::analysis::warnings::{CheckWarning, CheckWarnings};
+use super::clarity::docs::make_api_reference;
+use super::clarity::functions::{NativeFunctions, NativeVariables, DefineFunctions};
+use super::clarity::representations::{Span, SymbolicExpression};
+use super::clarity::types::{FunctionType, TypeSignature, Value};
+use super::contract_state::ContractState;
+use super::signature_help::parameter_offsets;
+use super::spans::{span_contains, span_to_range};
+use super::symbol_index::{SymbolIndex, DefinitionKind};
+use super::text_document::TextDocument;
+
+/// Names suggested in place of an unresolved one, closest first.
+const MAX_NAME_SUGGESTIONS: usize = 3;
+
+/// Value thrown by the checks wrapped around unchecked results, for the user to adapt.
+const PLACEHOLDER_ERROR: &str = "(err u0)";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuickFix {
+    pub title: String,
+    pub edits: Vec<TextEdit>,
+}
+
+impl QuickFix {
+    fn new(title: String, edits: Vec<TextEdit>) -> QuickFix {
+        QuickFix { title, edits }
+    }
+}
+
+/// Edits fixing a check error of the contract, when the error tells enough about the
+/// intent of the code.
+pub fn quick_fixes(state: &ContractState, error: &CheckError) -> Vec<QuickFix> {
+    let expr = match error.expressions.as_ref().and_then(|expressions| expressions.first()) {
+        Some(expr) => expr,
+        None => return vec![]
+    };
+    match error.err {
+        CheckErrors::UndefinedVariable(ref name) => rename_fixes(state, &expr.span, name, false),
+        CheckErrors::UndefinedFunction(ref name) | CheckErrors::UnknownFunction(ref name) => {
+            // the error is reported on the call, and on the definition containing it.
+            match find_call(expr, name) {
+                Some(call) => rename_fixes(state, &call.match_list().unwrap()[0].span, name, true),
+                None => vec![]
+            }
+        },
+        CheckErrors::PublicFunctionMustReturnResponse(_) => {
+            let body = match expr.match_list() {
+                Some(definition) if definition.len() == 3 => &definition[2],
+                _ => return vec![]
+            };
+            let range = span_to_range(&body.span);
+            vec![QuickFix::new("Wrap the returned value in (ok ...)".to_string(), vec![
+                TextEdit::new(Range::new(range.start, range.start), "(ok ".to_string()),
+                TextEdit::new(Range::new(range.end, range.end), ")".to_string()),
+            ])]
+        },
+        CheckErrors::IncorrectArgumentCount(expected, found) if found < expected => {
+            let call = match expr.match_list() {
+                Some(call) => call,
+                None => return vec![]
+            };
+            let placeholders = match call.first().and_then(|function| function.match_atom()) {
+                Some(function) => parameter_names(state, function.as_str()),
+                None => return vec![]
+            };
+            let missing = match placeholders.get(found..expected) {
+                Some(missing) => missing,
+                None => return vec![]
+            };
+            let end = span_to_range(&expr.span).end;
+            let before_closing = Position::new(end.line, end.character.saturating_sub(1));
+            let text: String = missing.iter().map(|name| format!(" {}", name)).collect();
+            vec![QuickFix::new(format!("Add the missing arguments: {}", missing.join(", ")), vec![
+                TextEdit::new(Range::new(before_closing, before_closing), text),
+            ])]
+        },
+        CheckErrors::WriteAttemptedInReadOnly => {
+            let define = match expr.match_list().and_then(|definition| definition.first()) {
+                Some(define) => define,
+                None => return vec![]
+            };
+            match define.match_atom().and_then(|name| DefineFunctions::lookup_by_name(name)) {
+                Some(DefineFunctions::ReadOnlyFunction) => vec![QuickFix::new(
+                    "Change to define-public".to_string(),
+                    vec![TextEdit::new(span_to_range(&define.span), DefineFunctions::PublicFunction.get_name())])],
+                _ => vec![]
+            }
+        },
+        CheckErrors::TypeError(TypeSignature::UIntType, TypeSignature::IntType) =>
+            literal_fixes(expr, true),
+        CheckErrors::TypeError(TypeSignature::IntType, TypeSignature::UIntType) =>
+            literal_fixes(expr, false),
+        _ => vec![]
+    }
+}
+
+/// Edits removing the code reported by a lint, or marking it as unused on purpose.
+pub fn warning_fixes(contract: &str, warning: &CheckWarning) -> Vec<QuickFix> {
+    let name = match warning.expressions.first() {
+        Some(name) => name,
+        None => return vec![]
+    };
+    match warning.warning {
+        CheckWarnings::UnusedPrivateFunction(ref atom) | CheckWarnings::UnusedConstant(ref atom)
+        | CheckWarnings::UnusedMap(ref atom) | CheckWarnings::UnusedDataVar(ref atom)
+        | CheckWarnings::UnusedToken(ref atom) => {
+            let definition = match warning.expressions.get(1) {
+                Some(definition) => definition,
+                None => return vec![]
+            };
+            let range = definition_removal(contract, definition);
+            vec![QuickFix::new(format!("Remove the definition of '{}'", atom), vec![TextEdit::new(range, String::new())])]
+        },
+        CheckWarnings::UnusedLetBinding(ref atom) => {
+            let mut fixes = vec![];
+            if let (Some(binding), Some(bindings)) = (warning.expressions.get(1), warning.expressions.get(2)) {
+                // removing the binding would drop the effects of its value.
+                let is_removable = binding.match_list().and_then(|pair| pair.get(1)).is_some_and(has_no_effects);
+                if let Some(range) = binding_removal(binding, bindings.match_list().unwrap_or_default()).filter(|_| is_removable) {
+                    fixes.push(QuickFix::new(format!("Remove the binding of '{}'", atom),
+                                             vec![TextEdit::new(range, String::new())]));
+                }
+            }
+            fixes.push(underscore_fix(name, atom));
+            fixes
+        },
+        CheckWarnings::UnusedParameter(ref atom) | CheckWarnings::UnusedMatchBinding(ref atom) =>
+            vec![underscore_fix(name, atom)],
+        CheckWarnings::UncheckedResponse => vec![
+            wrap_fix("Wrap in (try! ...)", name, "(try! ", ")"),
+            wrap_fix("Wrap in (unwrap! ...)", name, "(unwrap! ", &format!(" {})", PLACEHOLDER_ERROR)),
+        ],
+        CheckWarnings::IgnoredResult(_) => vec![
+            wrap_fix("Wrap in (asserts! ...)", name, "(asserts! ", &format!(" {})", PLACEHOLDER_ERROR)),
+        ],
+        CheckWarnings::TxSenderAuthorization => match name.match_atom() {
+            Some(atom) if matches!(NativeVariables::lookup_by_name(atom), Some(NativeVariables::TxSender)) => vec![QuickFix::new(
+                "Change to contract-caller".to_string(),
+                vec![TextEdit::new(span_to_range(&name.span), NativeVariables::ContractCaller.get_name())])],
+            _ => vec![]
+        },
+        CheckWarnings::CallerSuppliedRecipient(_) | CheckWarnings::CallerSuppliedSender(_) | CheckWarnings::UntrustedArgument(..)
+        | CheckWarnings::UIntUnderflow | CheckWarnings::DivisionByZero | CheckWarnings::ArithmeticOverflow(_) => vec![],
+        CheckWarnings::ConstantCondition(value) => {
+            let expr = match warning.expressions.get(1).and_then(|expr| expr.match_list()) {
+                Some(expr) => expr,
+                None => return vec![]
+            };
+            match expr.len() {
+                // (if condition then else)
+                4 => {
+                    let (title, branch) = if value { ("Replace with the then branch", &expr[2]) } else { ("Replace with the else branch", &expr[3]) };
+                    vec![keep_fix(title, &warning.expressions[1], branch)]
+                },
+                // (asserts! true thrown)
+                _ if value => vec![QuickFix::new("Replace with true".to_string(), vec![
+                    TextEdit::new(span_to_range(&warning.expressions[1].span), NativeVariables::NativeTrue.get_name())])],
+                // (asserts! false thrown), followed by the rest of its sequence. The thrown
+                // value can only replace it when the function returns the sequence.
+                _ => match (expr.get(2), warning.expressions.get(2), warning.expressions.get(3)) {
+                    (Some(thrown), Some(last), Some(_)) => {
+                        let (range, thrown, last) = (span_to_range(&warning.expressions[1].span), span_to_range(&thrown.span), span_to_range(&last.span));
+                        vec![QuickFix::new("Replace with the thrown value".to_string(), vec![
+                            TextEdit::new(Range::new(range.start, thrown.start), String::new()),
+                            TextEdit::new(Range::new(thrown.end, last.end), String::new()),
+                        ])]
+                    },
+                    _ => vec![]
+                }
+            }
+        },
+        CheckWarnings::ConstantMatch => match warning.expressions.get(1).and_then(|expr| expr.match_list()) {
+            // (match none some-name some-branch none-branch)
+            Some(expr) if expr.len() == 5 => vec![keep_fix("Replace with the none branch", &warning.expressions[1], &expr[4])],
+            _ => vec![]
+        },
+        CheckWarnings::UnreachableCode => match (warning.expressions.get(1), warning.expressions.get(2)) {
+            (Some(abort), Some(last)) => {
+                let range = Range::new(span_to_range(&abort.span).end, span_to_range(&last.span).end);
+                vec![QuickFix::new("Remove the unreachable code".to_string(), vec![TextEdit::new(range, String::new())])]
+            },
+            _ => vec![]
+        },
+        CheckWarnings::RedundantUnwrap(_) => {
+            // (try! (ok value))
+            let value = name.match_list().and_then(|call| call.get(1))
+                .and_then(|input| input.match_list()).and_then(|input| input.get(1));
+            match value {
+                Some(value) => vec![keep_fix("Replace with the unwrapped value", name, value)],
+                None => vec![]
+            }
+        },
+    }
+}
+
+/// Replaces the expression with one it contains.
+fn keep_fix(title: &str, expr: &SymbolicExpression, kept: &SymbolicExpression) -> QuickFix {
+    let (range, kept) = (span_to_range(&expr.span), span_to_range(&kept.span));
+    QuickFix::new(title.to_string(), vec![
+        TextEdit::new(Range::new(range.start, kept.start), String::new()),
+        TextEdit::new(Range::new(kept.end, range.end), String::new()),
+    ])
+}
+
+fn wrap_fix(title: &str, expr: &SymbolicExpression, before: &str, after: &str) -> QuickFix {
+    let range = span_to_range(&expr.span);
+    QuickFix::new(title.to_string(), vec![
+        TextEdit::new(Range::new(range.start, range.start), before.to_string()),
+        TextEdit::new(Range::new(range.end, range.end), after.to_string()),
+    ])
+}
+
+/// Range of a definition: the whole lines when the definition stands on lines of its
+/// own, otherwise the definition and the spaces separating it from what follows or
+/// precedes it.
+fn definition_removal(contract: &str, definition: &SymbolicExpression) -> Range {
+    let document = TextDocument::new(contract.to_string());
+    let text = document.text();
+    let range = span_to_range(&definition.span);
+    let (start, end) = (document.offset_at(range.start), document.offset_at(range.end));
+    let line_start = text[..start].rfind('\n').map_or(0, |index| index + 1);
+    let line_end = text[end..].find('\n').map_or(text.len(), |index| end + index + 1);
+    let (before, after) = (&text[line_start..start], &text[end..line_end]);
+
+    let is_space = |c: char| c == ' ' || c == '\t';
+    let (start, end) = if before.trim().is_empty() && after.trim().is_empty() {
+        (line_start, line_end)
+    } else if !after.trim().is_empty() {
+        (start, end + after.len() - after.trim_start_matches(is_space).len())
+    } else {
+        (start - (before.len() - before.trim_end_matches(is_space).len()), end)
+    };
+    Range::new(document.position_at(start), document.position_at(end))
+}
+
+/// Range of a let binding along with the whitespace separating it from its neighbour,
+/// unless it is the only binding.
+fn binding_removal(binding: &SymbolicExpression, bindings: &[SymbolicExpression]) -> Option<Range> {
+    let index = bindings.iter().position(|other| other.span == binding.span)?;
+    let range = span_to_range(&binding.span);
+    if let Some(next) = bindings.get(index + 1) {
+        return Some(Range::new(range.start, span_to_range(&next.span).start));
+    }
+    let previous = bindings.get(index.checked_sub(1)?)?;
+    Some(Range::new(span_to_range(&previous.span).end, range.end))
+}
+
+/// Whether evaluating the expression does nothing but compute its value: no write, no
+/// transfer, no print, no unwrap or arithmetic which could abort, and no call to a
+/// function.
+fn has_no_effects(expr: &SymbolicExpression) -> bool {
+    use super::clarity::functions::NativeFunctions::*;
+
+    let list = match expr.match_list() {
+        Some(list) => list,
+        None => return true
+    };
+    let (function, args) = match list.split_first() {
+        Some((function, args)) => (function.match_atom().and_then(|name| NativeFunctions::lookup_by_name(name)), args),
+        None => return true
+    };
+    match function {
+        // (let ((name value)...) body...), (tuple (key value)...)
+        Some(Let) => args.iter().enumerate().all(|(index, arg)| match arg.match_list() {
+            Some(bindings) if index == 0 => bindings.iter()
+                .all(|binding| binding.match_list().and_then(|pair| pair.get(1)).is_some_and(has_no_effects)),
+            _ => has_no_effects(arg)
+        }),
+        Some(TupleCons) => args.iter()
+            .all(|pair| pair.match_list().and_then(|pair| pair.get(1)).is_some_and(has_no_effects)),
+        Some(CmpGeq) | Some(CmpLeq) | Some(CmpLess) | Some(CmpGreater) | Some(Equals) | Some(And) | Some(Or) | Some(Not)
+        | Some(If) | Some(Begin) | Some(Match) | Some(Append) | Some(Concat) | Some(AsMaxLen)
+        | Some(Len) | Some(ListCons) | Some(TupleGet) | Some(Hash160) | Some(Sha256) | Some(Sha512) | Some(Sha512Trunc256)
+        | Some(Keccak256) | Some(ConsSome) | Some(ConsOkay) | Some(ConsError) | Some(DefaultTo) | Some(IsOkay)
+        | Some(IsNone) | Some(IsErr) | Some(IsSome) | Some(FetchVar) | Some(FetchEntry) | Some(GetBlockInfo)
+        | Some(GetTokenBalance) | Some(GetAssetOwner) | Some(GetStxBalance) => args.iter().all(has_no_effects),
+        _ => false
+    }
+}
+
+fn underscore_fix(name: &SymbolicExpression, atom: &str) -> QuickFix {
+    let end = span_to_range(&name.span).end;
+    QuickFix::new(format!("Rename to '{}_'", atom), vec![TextEdit::new(Range::new(end, end), "_".to_string())])
+}
+
+/// The call to the function, in the expression or the lists it contains.
+fn find_call<'a>(expr: &'a SymbolicExpression, function: &str) -> Option<&'a SymbolicExpression> {
+    let list = expr.match_list()?;
+    if list.first().and_then(|head| head.match_atom()).map(|head| head.as_str()) == Some(function) {
+        return Some(expr);
+    }
+    list.iter().find_map(|child| find_call(child, function))
+}
+
+/// Replaces an unresolved name with the names in scope closest to it.
+fn rename_fixes(state: &ContractState, span: &Span, name: &str, is_function: bool) -> Vec<QuickFix> {
+    let index = SymbolIndex::new(&state.contract_ast);
+    let position = span_to_range(span).start;
+    let mut candidates: Vec<&str> = index.definitions.iter()
+        .filter(|definition| match definition.scope {
+            Some(ref scope) => span_contains(scope, &position),
+            None => true
+        })
+        .filter(|definition| match definition.kind {
+            DefinitionKind::PrivateFunction | DefinitionKind::PublicFunction | DefinitionKind::ReadOnlyFunction =>
+                is_function,
+            DefinitionKind::Constant | DefinitionKind::Parameter | DefinitionKind::LetBinding | DefinitionKind::MatchBinding =>
+                !is_function,
+            _ => false
+        })
+        .map(|definition| definition.name.as_str())
+        .collect();
+    if is_function {
+        candidates.extend(NativeFunctions::ALL_NAMES);
+    } else {
+        candidates.extend(NativeVariables::ALL_NAMES);
+    }
+
+    let max_distance = std::cmp::max(1, name.len() / 3);
+    let mut suggestions: Vec<_> = candidates.into_iter()
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+    suggestions.sort();
+    suggestions.dedup();
+    suggestions.into_iter()
+        .take(MAX_NAME_SUGGESTIONS)
+        .map(|(_, candidate)| QuickFix::new(
+            format!("Change to '{}'", candidate),
+            vec![TextEdit::new(span_to_range(span), candidate.to_string())]))
+        .collect()
+}
+
+/// Names of the parameters of the function, to be used as placeholders.
+fn parameter_names(state: &ContractState, function: &str) -> Vec<String> {
+    if let Some(native_function) = NativeFunctions::lookup_by_name(function) {
+        let api = make_api_reference(&native_function);
+        // functions with several forms have no single list of parameters.
+        if api.signature.contains('|') {
+            return vec![];
+        }
+        return parameter_offsets(&api.signature).into_iter()
+            .map(|(start, end)| api.signature[start..end].to_string())
+            .collect();
+    }
+    let function_type = state.analysis.get_private_function(function)
+        .or_else(|| state.analysis.get_public_function_type(function))
+        .or_else(|| state.analysis.get_read_only_function_type(function));
+    match function_type {
+        Some(FunctionType::Fixed(function)) => function.args.iter()
+            .map(|arg| arg.name.to_string())
+            .collect(),
+        _ => vec![]
+    }
+}
+
+/// Converts the literals of the expression, or the expression itself, between int and
+/// uint.
+fn literal_fixes(expr: &SymbolicExpression, to_uint: bool) -> Vec<QuickFix> {
+    let literals = match expr.match_list() {
+        Some(list) => list.iter().collect(),
+        None => vec![expr]
+    };
+    let edits: Vec<_> = literals.into_iter()
+        .filter_map(|literal| {
+            let value = literal.match_atom_value().or_else(|| literal.match_literal_value())?;
+            let converted = match value {
+                Value::Int(value) if to_uint && *value >= 0 => format!("u{}", value),
+                Value::UInt(value) if !to_uint && *value <= i128::MAX as u128 => value.to_string(),
+                _ => return None
+            };
+            Some(TextEdit::new(span_to_range(&literal.span), converted))
+        })
+        .collect();
+    if edits.is_empty() {
+        return vec![];
+    }
+    let title = match (to_uint, edits.len()) {
+        (true, 1) => format!("Convert {} to uint", edits[0].new_text),
+        (true, _) => "Convert the literals to uint".to_string(),
+        (false, 1) => format!("Convert {} to int", edits[0].new_text),
+        (false, _) => "Convert the literals to int".to_string(),
+    };
+    vec![QuickFix::new(title, edits)]
+}
+
+/// Levenshtein distance between the names.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut distances: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut previous_diagonal = distances[0];
+        distances[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous_diagonal + if a_char == *b_char { 0 } else { 1 };
+            previous_diagonal = distances[j + 1];
+            distances[j + 1] = substitution.min(distances[j] + 1).min(distances[j + 1] + 1);
+        }
+    }
+    distances[b.len()]
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    /// Titles and new texts of the fixes for the first check error of the contract.
+    fn fixes(contract: &str) -> Vec<(String, Vec<(Range, String)>)> {
+        let state = ContractState::new(contract);
+        quick_fixes(&state, &state.check_errors[0]).into_iter()
+            .map(|fix| (fix.title, fix.edits.into_iter().map(|edit| (edit.range, edit.new_text)).collect()))
+            .collect()
+    }
+
+    fn range(line: u64, start: u64, end: u64) -> Range {
+        Range::new(Position::new(line, start), Position::new(line, end))
+    }
+
+    #[test]
+    fn test_unresolved_names_are_corrected() {
+        let contract = "(define-private (get-balance) u1)\n(define-read-only (f (amount uint)) (+ amont (get-balanse)))";
+        let state = ContractState::new(contract);
+        let all_fixes: Vec<_> = state.check_errors.iter()
+            .flat_map(|error| quick_fixes(&state, error))
+            .collect();
+        assert!(all_fixes.contains(&QuickFix::new("Change to 'amount'".to_string(),
+                                                  vec![TextEdit::new(range(1, 39, 44), "amount".to_string())])));
+        assert!(all_fixes.iter().any(|fix| fix.title == "Change to 'get-balance'"
+            && fix.edits == vec![TextEdit::new(range(1, 46, 57), "get-balance".to_string())]));
+
+        assert_eq!(fixes("(define-read-only (f) (ok tx-sendr))")[0].0, "Change to 'tx-sender'");
+        assert_eq!(fixes("(define-read-only (f) (ok (is-eqq u1 u1)))")[0].0, "Change to 'is-eq'");
+        assert!(fixes("(define-read-only (f) (ok xyz))").is_empty());
+    }
+
+    #[test]
+    fn test_definitions_are_fixed() {
+        assert_eq!(fixes("(define-public (f) (begin (print u1) u1))"), vec![
+            ("Wrap the returned value in (ok ...)".to_string(), vec![
+                (range(0, 19, 19), "(ok ".to_string()),
+                (range(0, 40, 40), ")".to_string())])]);
+        assert_eq!(fixes("(define-data-var v uint u0)\n(define-read-only (f) (var-set v u1))"), vec![
+            ("Change to define-public".to_string(), vec![(range(1, 1, 17), "define-public".to_string())])]);
+    }
+
+    #[test]
+    fn test_missing_arguments_are_added() {
+        assert_eq!(fixes("(define-private (g (a uint) (b int)) a)\n(define-read-only (f) (g u1))"), vec![
+            ("Add the missing arguments: b".to_string(), vec![(range(1, 27, 27), " b".to_string())])]);
+        assert_eq!(fixes("(define-read-only (f) (ok (var-get)))")[0].1, vec![(range(0, 34, 34), " var-name".to_string())]);
+    }
+
+    #[test]
+    fn test_literals_are_converted() {
+        assert_eq!(fixes("(define-read-only (f (a uint)) (+ a 2 3))"), vec![
+            ("Convert the literals to uint".to_string(), vec![
+                (range(0, 36, 37), "u2".to_string()),
+                (range(0, 38, 39), "u3".to_string())])]);
+        assert_eq!(fixes("(define-data-var v int u1)"), vec![
+            ("Convert 1 to int".to_string(), vec![(range(0, 23, 25), "1".to_string())])]);
+        assert!(fixes("(define-data-var v uint -1)").is_empty());
+    }
+
+    /// Titles and new texts of the fixes for the lint warnings of the contract.
+    fn lint_fixes(contract: &str) -> Vec<(String, Vec<(Range, String)>)> {
+        let state = ContractState::new(contract);
+        state.check_warnings.iter()
+            .flat_map(|warning| warning_fixes(contract, warning))
+            .map(|fix| (fix.title, fix.edits.into_iter().map(|edit| (edit.range, edit.new_text)).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn test_unused_code_is_removed() {
+        let contract = "(define-constant limit u10)\n\
+                        (define-read-only (f (a uint))\n  (let ((x u1) (y u2))\n    x))";
+        assert_eq!(lint_fixes(contract), vec![
+            ("Remove the definition of 'limit'".to_string(), vec![
+                (Range::new(Position::new(0, 0), Position::new(1, 0)), String::new())]),
+            ("Rename to 'a_'".to_string(), vec![(range(1, 23, 23), "_".to_string())]),
+            ("Remove the binding of 'y'".to_string(), vec![(range(2, 14, 21), String::new())]),
+            ("Rename to 'y_'".to_string(), vec![(range(2, 17, 17), "_".to_string())]),
+        ]);
+        assert_eq!(lint_fixes("(define-read-only (f) (let ((x u1)) u2))"), vec![
+            ("Rename to 'x_'".to_string(), vec![(range(0, 30, 30), "_".to_string())])]);
+        // the transfer is kept.
+        assert_eq!(lint_fixes("(define-public (f) (let ((r (try! (stx-transfer? u1 tx-sender tx-sender))) (x u1)) (ok x)))"), vec![
+            ("Rename to 'r_'".to_string(), vec![(range(0, 27, 27), "_".to_string())])]);
+        // so is the subtraction, which aborts on an underflow.
+        assert_eq!(lint_fixes("(define-read-only (f (a uint)) (let ((d (- a u1)) (x u1)) x))"), vec![
+            ("Rename to 'd_'".to_string(), vec![(range(0, 39, 39), "_".to_string())])]);
+    }
+
+    #[test]
+    fn test_definitions_sharing_lines_are_removed_alone() {
+        let contract = "(define-constant a u1) (define-constant b u2) ;; limits\n(define-read-only (f) u3)";
+        assert_eq!(lint_fixes(contract), vec![
+            ("Remove the definition of 'a'".to_string(), vec![(range(0, 0, 23), String::new())]),
+            ("Remove the definition of 'b'".to_string(), vec![(range(0, 23, 46), String::new())]),
+        ]);
+    }
+
+    #[test]
+    fn test_dropped_results_are_checked() {
+        let contract = "(define-map claimed ((who principal)) ((done bool)))\n\
+                        (define-public (claim)\n  (begin\n    (map-insert claimed {who: tx-sender} {done: true})\n    (stx-transfer? u1 tx-sender tx-sender)))\n\
+                        (define-public (f) (begin (claim) (ok true)))";
+        assert_eq!(lint_fixes(contract), vec![
+            ("Wrap in (asserts! ...)".to_string(), vec![
+                (range(3, 4, 4), "(asserts! ".to_string()),
+                (range(3, 54, 54), " (err u0))".to_string())]),
+            ("Wrap in (try! ...)".to_string(), vec![
+                (range(5, 26, 26), "(try! ".to_string()),
+                (range(5, 33, 33), ")".to_string())]),
+            ("Wrap in (unwrap! ...)".to_string(), vec![
+                (range(5, 26, 26), "(unwrap! ".to_string()),
+                (range(5, 33, 33), " (err u0))".to_string())]),
+        ]);
+    }
+
+    #[test]
+    fn test_tx_sender_checks_are_changed() {
+        let contract = "(define-constant owner tx-sender)\n\
+                        (define-public (f) (begin (asserts! (is-eq tx-sender owner) (err u1)) (ok true)))";
+        assert_eq!(lint_fixes(contract), vec![
+            ("Change to contract-caller".to_string(), vec![(range(1, 43, 52), "contract-caller".to_string())])]);
+    }
+
+    #[test]
+    fn test_dead_code_is_simplified() {
+        let contract = "(define-read-only (f (a uint))\n  (begin\n    (asserts! false (err u1))\n    (print a)\n    (ok (try! (ok a)))))\n\
+                        (define-read-only (g (a uint)) (if true (ok a) (err u1)))";
+        assert_eq!(lint_fixes(contract), vec![
+            ("Replace with the thrown value".to_string(), vec![
+                (range(2, 4, 20), String::new()),
+                (Range::new(Position::new(2, 28), Position::new(4, 22)), String::new())]),
+            ("Replace with the unwrapped value".to_string(), vec![
+                (range(4, 8, 18), String::new()),
+                (range(4, 19, 21), String::new())]),
+            ("Replace with the then branch".to_string(), vec![
+                (range(5, 31, 40), String::new()),
+                (range(5, 46, 56), String::new())]),
+        ]);
+        assert_eq!(lint_fixes("(define-public (g)\n  (begin\n    (unwrap-panic (err u1))\n    (print u2)\n    (ok u3)))"), vec![
+            ("Remove the unreachable code".to_string(), vec![
+                (Range::new(Position::new(2, 27), Position::new(3, 14)), String::new())])]);
+        // the inner sequence is not returned: the thrown value would be dropped.
+        assert_eq!(lint_fixes("(define-public (h (a uint))\n  (begin\n    (begin\n      (asserts! false (err u1))\n      (print a))\n    (ok a)))"), vec![]);
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("balance", "balanse"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+}
@@ -0,0 +1,153 @@
+use std::convert::TryFrom;
+
+use tower_lsp::lsp_types::TextEdit;
+
+use super::clarity::analysis::CheckErrors;
+use super::clarity::diagnostic::DiagnosableError;
+use super::clarity::functions::{NativeFunctions, DefineFunctions, NativeVariables};
+use super::clarity::representations::{ClarityName, PreSymbolicExpression, PreSymbolicExpressionType, Span};
+use super::clarity::types::{PrincipalData, StandardPrincipalData, Value};
+use super::spans::{span_to_range, spans_overlap};
+use super::symbol_index::SymbolIndex;
+
+/// Checks that a definition can take the new name: it must be a valid Clarity name, not
+/// reserved by the language, and not clash with a name the checker would reject it next to.
+pub fn validate_new_name(index: &SymbolIndex, definition: usize, new_name: &str) -> Result<ClarityName, String> {
+    let name = ClarityName::try_from(new_name.to_string())
+        .map_err(|_| format!("'{}' is not a valid name", new_name))?;
+
+    let is_reserved = NativeFunctions::lookup_by_name(new_name).is_some()
+        || DefineFunctions::lookup_by_name(new_name).is_some()
+        || NativeVariables::lookup_by_name(new_name).is_some();
+    if is_reserved {
+        return Err(format!("'{}' is a reserved name", new_name));
+    }
+
+    // Like `ContractContext::check_name_used`, top-level names must be unique, and local
+    // bindings can not shadow any name visible where they are bound.
+    let renamed = &index.definitions[definition];
+    let is_used = index.definitions.iter().enumerate().any(|(other_index, other)| {
+        other_index != definition && other.name == name && match (&renamed.scope, &other.scope) {
+            (Some(scope), Some(other_scope)) => spans_overlap(scope, other_scope),
+            _ => true
+        }
+    });
+    if is_used {
+        return Err(CheckErrors::NameAlreadyUsed(new_name.to_string()).message());
+    }
+
+    Ok(name)
+}
+
+/// Edits renaming a definition and all the references to it.
+pub fn rename_edits(index: &SymbolIndex, definition: usize, new_name: &ClarityName) -> Vec<TextEdit> {
+    let mut spans = vec![&index.definitions[definition].name_span];
+    spans.extend(index.references_to(definition).map(|reference| &reference.span));
+    spans.into_iter()
+        .map(|span| TextEdit::new(span_to_range(span), new_name.to_string()))
+        .collect()
+}
+
+/// Spans of the function names in `contract-call?` expressions calling the function
+/// of the given contract. Workspace contracts are analysed as deployed by the transient
+/// principal: calls to a contract of the same name deployed by anyone else are left out.
+pub fn contract_call_sites(pre_expressions: &[PreSymbolicExpression], contract_name: &str, function_name: &str) -> Vec<Span> {
+    let mut sites = vec![];
+    for expr in pre_expressions.iter() {
+        collect_contract_call_sites(expr, contract_name, function_name, &mut sites);
+    }
+    sites
+}
+
+fn collect_contract_call_sites(expr: &PreSymbolicExpression, contract_name: &str, function_name: &str, sites: &mut Vec<Span>) {
+    let list = match expr.pre_expr {
+        PreSymbolicExpressionType::List(ref list) | PreSymbolicExpressionType::Tuple(ref list) => list,
+        _ => return
+    };
+
+    let is_contract_call = list.first()
+        .and_then(|head| head.match_atom())
+        .map(|name| name.as_str() == NativeFunctions::ContractCall.get_name())
+        .unwrap_or(false);
+    if is_contract_call && list.len() > 2 {
+        let callee = match list[1].pre_expr {
+            PreSymbolicExpressionType::SugaredContractIdentifier(ref name) => Some(name.as_str()),
+            PreSymbolicExpressionType::AtomValue(Value::Principal(PrincipalData::Contract(ref identifier)))
+                if identifier.issuer == StandardPrincipalData::transient() => Some(identifier.name.as_str()),
+            _ => None
+        };
+        let function = list[2].match_atom().map(|name| name.as_str());
+        if callee == Some(contract_name) && function == Some(function_name) {
+            sites.push(list[2].span.clone());
+        }
+    }
+
+    for item in list.iter() {
+        collect_contract_call_sites(item, contract_name, function_name, sites);
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::clarity::ast::parser::parse_with_recovery;
+    use crate::contract_state::ContractState;
+    use tower_lsp::lsp_types::Position;
+
+    fn validate(contract: &str, position: Position, new_name: &str) -> Result<ClarityName, String> {
+        let state = ContractState::new(contract);
+        let index = SymbolIndex::new(&state.contract_ast);
+        let definition = index.definition_at(&position).unwrap();
+        validate_new_name(&index, definition, new_name)
+    }
+
+    #[test]
+    fn test_new_names_are_validated() {
+        let contract = "(define-constant limit u10)\n\
+                        (define-private (f (a uint)) (let ((b u1)) (+ a b limit)))\n\
+                        (define-private (g (c uint)) c)";
+        let a = Position::new(1, 20);
+        assert!(validate(contract, a, "amount").is_ok());
+        assert_eq!(validate(contract, a, "1st"), Err("'1st' is not a valid name".to_string()));
+        assert_eq!(validate(contract, a, "map"), Err("'map' is a reserved name".to_string()));
+        assert_eq!(validate(contract, a, "tx-sender"), Err("'tx-sender' is a reserved name".to_string()));
+        assert_eq!(validate(contract, a, "limit"), Err("defining 'limit' conflicts with previous value".to_string()));
+        assert_eq!(validate(contract, a, "b"), Err("defining 'b' conflicts with previous value".to_string()));
+        // bindings of other functions are not in scope.
+        assert!(validate(contract, a, "c").is_ok());
+        // top-level definitions can not be shadowed by any binding.
+        assert!(validate(contract, Position::new(0, 17), "c").is_err());
+    }
+
+    #[test]
+    fn test_trait_references_are_renamed() {
+        let contract = "(define-trait token-trait ((transfer (uint) (response bool uint))))\n\
+                        (define-public (send (token <token-trait>)) (ok true))";
+        let state = ContractState::new(contract);
+        let index = SymbolIndex::new(&state.contract_ast);
+        let definition = index.definition_at(&Position::new(0, 15)).unwrap();
+        let new_name = validate_new_name(&index, definition, "tok").unwrap();
+        let edits: Vec<_> = rename_edits(&index, definition, &new_name).into_iter()
+            .map(|edit| (edit.range.start.line, edit.range.start.character, edit.range.end.character, edit.new_text))
+            .collect();
+        assert_eq!(edits, vec![
+            (0, 14, 25, "tok".to_string()),
+            (1, 29, 40, "tok".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_contract_call_sites() {
+        let caller = format!("(define-public (proxy)\n\
+                              \x20 (begin\n\
+                              \x20   (try! (contract-call? .counter increment u1))\n\
+                              \x20   (try! (contract-call? 'SP000000000000000000002Q6VF78.counter increment u2))\n\
+                              \x20   (contract-call? '{}.counter increment u3)))\n\
+                              (define-read-only (other) (contract-call? .other-contract increment u1))",
+                             StandardPrincipalData::transient());
+        let (pre_expressions, _) = parse_with_recovery(&caller);
+        let sites = contract_call_sites(&pre_expressions, "counter", "increment");
+        let lines: Vec<_> = sites.iter().map(|span| (span.start_line, span.start_column)).collect();
+        assert_eq!(lines, vec![(3, 36), (5, 70)]);
+    }
+}
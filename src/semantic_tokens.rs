@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::{
+    Range,
+    SemanticToken,
+    SemanticTokenModifier,
+    SemanticTokenType,
+    SemanticTokensLegend};
+
+use super::clarity::ast::parser::{lex_with_recovery, LexItem};
+use super::clarity::functions::{NativeFunctions, DefineFunctions, NativeVariables};
+use super::clarity::types::Value;
+use super::contract_state::ContractState;
+use super::spans::span_to_range;
+use super::symbol_index::{SymbolIndex, DefinitionKind};
+use super::text_document::TextDocument;
+
+const PRINCIPAL: SemanticTokenType = SemanticTokenType::new("principal");
+const BUFFER: SemanticTokenType = SemanticTokenType::new("buffer");
+
+/// Token types, in the order of the legend.
+const TOKEN_TYPES: [SemanticTokenType; 11] = [
+    SemanticTokenType::FUNCTION,
+    SemanticTokenType::KEYWORD,
+    SemanticTokenType::STRUCT,
+    SemanticTokenType::VARIABLE,
+    SemanticTokenType::PARAMETER,
+    SemanticTokenType::TYPE,
+    SemanticTokenType::INTERFACE,
+    SemanticTokenType::NUMBER,
+    SemanticTokenType::STRING,
+    PRINCIPAL,
+    BUFFER,
+];
+
+const DEFAULT_LIBRARY: SemanticTokenModifier = SemanticTokenModifier::new("defaultLibrary");
+const PUBLIC: SemanticTokenModifier = SemanticTokenModifier::new("public");
+const READ_ONLY_FUNCTION: SemanticTokenModifier = SemanticTokenModifier::new("readOnlyFunction");
+const PRIVATE: SemanticTokenModifier = SemanticTokenModifier::new("private");
+const LOCAL: SemanticTokenModifier = SemanticTokenModifier::new("local");
+
+/// Token modifiers, in the order of the legend: the modifiers of a token are a bitset
+/// of their indices.
+const TOKEN_MODIFIERS: [SemanticTokenModifier; 8] = [
+    SemanticTokenModifier::DECLARATION,
+    SemanticTokenModifier::READONLY,
+    SemanticTokenModifier::STATIC,
+    DEFAULT_LIBRARY,
+    PUBLIC,
+    READ_ONLY_FUNCTION,
+    PRIVATE,
+    LOCAL,
+];
+
+pub fn legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: TOKEN_TYPES.to_vec(),
+        token_modifiers: TOKEN_MODIFIERS.to_vec(),
+    }
+}
+
+/// Classifies the tokens of the contract, or only the ones starting in the range. The
+/// lexer tells literals apart, while names are classified by what they resolve to.
+pub fn semantic_tokens(contract: &str, range: Option<&Range>) -> Vec<SemanticToken> {
+    let state = ContractState::new(contract);
+    let index = SymbolIndex::new(&state.contract_ast);
+
+    // definitions by the position of their names and of the references to them.
+    let mut resolved = HashMap::new();
+    for definition in index.definitions.iter() {
+        resolved.insert((definition.name_span.start_line, definition.name_span.start_column), (definition.kind, true));
+    }
+    for reference in index.references.iter() {
+        let kind = index.definitions[reference.definition].kind;
+        resolved.insert((reference.span.start_line, reference.span.start_column), (kind, false));
+    }
+
+    // the lexer counts columns in UTF-16 code units, like LSP positions.
+    let document = TextDocument::new(contract.to_string());
+    let mut tokens = vec![];
+    let (mut previous_line, mut previous_start) = (0, 0);
+    for (item, span) in lex_with_recovery(contract) {
+        let token_range = span_to_range(&span);
+        if let Some(range) = range {
+            if token_range.start < range.start || token_range.start >= range.end {
+                continue;
+            }
+        }
+
+        let classification = match item {
+            LexItem::Variable(ref name) => match resolved.get(&(span.start_line, span.start_column)) {
+                Some((kind, is_declaration)) => Some(classify_definition(*kind, *is_declaration)),
+                None => classify_reserved_name(name)
+            },
+            LexItem::LiteralValue(_, Value::Int(_)) | LexItem::LiteralValue(_, Value::UInt(_)) =>
+                Some((SemanticTokenType::NUMBER, vec![])),
+            LexItem::LiteralValue(_, Value::Principal(_)) | LexItem::SugaredContractIdentifier(..) =>
+                Some((PRINCIPAL, vec![])),
+            LexItem::LiteralValue(_, Value::Buffer(_)) => {
+                // string literals are buffers too.
+                let is_hex = contract[document.offset_at(token_range.start)..].starts_with("0x");
+                if is_hex {
+                    Some((BUFFER, vec![]))
+                } else {
+                    Some((SemanticTokenType::STRING, vec![]))
+                }
+            },
+            LexItem::TraitReference(..) | LexItem::FieldIdentifier(..) | LexItem::SugaredFieldIdentifier(..) =>
+                Some((SemanticTokenType::INTERFACE, vec![])),
+            _ => None
+        };
+
+        if let Some((token_type, modifiers)) = classification {
+            let line = token_range.start.line as u32;
+            let start = token_range.start.character as u32;
+            let delta_start = if line == previous_line { start - previous_start } else { start };
+            tokens.push(SemanticToken {
+                delta_line: line - previous_line,
+                delta_start,
+                length: (token_range.end.character - token_range.start.character) as u32,
+                token_type: legend_index(&TOKEN_TYPES, &token_type),
+                token_modifiers_bitset: modifiers.iter()
+                    .fold(0, |bitset, modifier| bitset | 1 << legend_index(&TOKEN_MODIFIERS, modifier)),
+            });
+            previous_line = line;
+            previous_start = start;
+        }
+    }
+    tokens
+}
+
+fn classify_definition(kind: DefinitionKind, is_declaration: bool) -> (SemanticTokenType, Vec<SemanticTokenModifier>) {
+    let (token_type, mut modifiers) = match kind {
+        DefinitionKind::PublicFunction => (SemanticTokenType::FUNCTION, vec![PUBLIC]),
+        DefinitionKind::ReadOnlyFunction => (SemanticTokenType::FUNCTION, vec![READ_ONLY_FUNCTION]),
+        DefinitionKind::PrivateFunction => (SemanticTokenType::FUNCTION, vec![PRIVATE]),
+        DefinitionKind::Constant => (SemanticTokenType::VARIABLE, vec![SemanticTokenModifier::READONLY, SemanticTokenModifier::STATIC]),
+        DefinitionKind::DataVar => (SemanticTokenType::VARIABLE, vec![SemanticTokenModifier::STATIC]),
+        DefinitionKind::Map => (SemanticTokenType::STRUCT, vec![]),
+        DefinitionKind::FungibleToken | DefinitionKind::NonFungibleToken => (SemanticTokenType::TYPE, vec![]),
+        DefinitionKind::Trait | DefinitionKind::UseTrait => (SemanticTokenType::INTERFACE, vec![]),
+        DefinitionKind::Parameter => (SemanticTokenType::PARAMETER, vec![]),
+        DefinitionKind::LetBinding | DefinitionKind::MatchBinding => (SemanticTokenType::VARIABLE, vec![LOCAL]),
+    };
+    if is_declaration {
+        modifiers.push(SemanticTokenModifier::DECLARATION);
+    }
+    (token_type, modifiers)
+}
+
+fn classify_reserved_name(name: &str) -> Option<(SemanticTokenType, Vec<SemanticTokenModifier>)> {
+    if NativeFunctions::lookup_by_name(name).is_some() {
+        Some((SemanticTokenType::FUNCTION, vec![DEFAULT_LIBRARY]))
+    } else if DefineFunctions::lookup_by_name(name).is_some() {
+        Some((SemanticTokenType::KEYWORD, vec![]))
+    } else if NativeVariables::lookup_by_name(name).is_some() {
+        Some((SemanticTokenType::VARIABLE, vec![DEFAULT_LIBRARY, SemanticTokenModifier::READONLY]))
+    } else {
+        None
+    }
+}
+
+fn legend_index<T: PartialEq>(legend: &[T], entry: &T) -> u32 {
+    legend.iter().position(|legend_entry| legend_entry == entry).unwrap() as u32
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use tower_lsp::lsp_types::Position;
+
+    /// Decodes the tokens to their position, length, type and modifiers.
+    fn decode(tokens: Vec<SemanticToken>) -> Vec<(u32, u32, u32, String, Vec<String>)> {
+        let (mut line, mut start) = (0, 0);
+        tokens.into_iter().map(|token| {
+            if token.delta_line > 0 {
+                start = 0;
+            }
+            line += token.delta_line;
+            start += token.delta_start;
+            let modifiers = TOKEN_MODIFIERS.iter().enumerate()
+                .filter(|(index, _)| token.token_modifiers_bitset & (1 << index) != 0)
+                .map(|(_, modifier)| modifier.as_str().to_string())
+                .collect();
+            (line, start, token.length, TOKEN_TYPES[token.token_type as usize].as_str().to_string(), modifiers)
+        }).collect()
+    }
+
+    fn token(line: u32, start: u32, length: u32, token_type: &str, modifiers: &[&str]) -> (u32, u32, u32, String, Vec<String>) {
+        (line, start, length, token_type.to_string(), modifiers.iter().map(|modifier| modifier.to_string()).collect())
+    }
+
+    #[test]
+    fn test_semantic_tokens() {
+        let contract = "(define-map balances principal uint)\n\
+                        (define-public (pay (amount uint))\n\
+                        \x20 (let ((fee u10))\n\
+                        \x20   (map-set balances tx-sender (+ amount fee -1))\n\
+                        \x20   (ok 'SP000000000000000000002Q6VF78)))\n\
+                        (define-constant hash 0x0102 \"text\")";
+        let tokens = decode(semantic_tokens(contract, None));
+        assert_eq!(tokens, vec![
+            token(0, 1, 10, "keyword", &[]),
+            token(0, 12, 8, "struct", &["declaration"]),
+            token(1, 1, 13, "keyword", &[]),
+            token(1, 16, 3, "function", &["declaration", "public"]),
+            token(1, 21, 6, "parameter", &["declaration"]),
+            token(2, 3, 3, "function", &["defaultLibrary"]),
+            token(2, 9, 3, "variable", &["declaration", "local"]),
+            token(2, 13, 3, "number", &[]),
+            token(3, 5, 7, "function", &["defaultLibrary"]),
+            token(3, 13, 8, "struct", &[]),
+            token(3, 22, 9, "variable", &["readonly", "defaultLibrary"]),
+            token(3, 33, 1, "function", &["defaultLibrary"]),
+            token(3, 35, 6, "parameter", &[]),
+            token(3, 42, 3, "variable", &["local"]),
+            token(3, 46, 2, "number", &[]),
+            token(4, 5, 2, "function", &["defaultLibrary"]),
+            token(4, 8, 30, "principal", &[]),
+            token(5, 1, 15, "keyword", &[]),
+            token(5, 17, 4, "variable", &["declaration", "readonly", "static"]),
+            token(5, 22, 6, "buffer", &[]),
+            token(5, 29, 6, "string", &[]),
+        ]);
+
+        let range = Range::new(Position::new(3, 0), Position::new(4, 0));
+        let tokens = decode(semantic_tokens(contract, Some(&range)));
+        assert_eq!(tokens.len(), 7);
+        assert_eq!(tokens[0], token(3, 5, 7, "function", &["defaultLibrary"]));
+    }
+
+    #[test]
+    fn test_buffers_after_non_ascii_text() {
+        // strings are ASCII: "é" is left unclassified.
+        let tokens = decode(semantic_tokens("(list \"é\" 0x01 \"0x\")", None));
+        let types: Vec<_> = tokens.iter().map(|token| token.3.as_str()).collect();
+        assert_eq!(types, vec!["function", "buffer", "string"]);
+    }
+}
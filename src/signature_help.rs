@@ -0,0 +1,284 @@
+use tower_lsp::lsp_types::*;
+
+use super::call_context::{open_lists, OpenList};
+use super::clarity::docs::{make_api_reference, make_define_reference, FunctionAPI};
+use super::clarity::functions::{NativeFunctions, DefineFunctions};
+use super::clarity::types::{FixedFunction, FunctionType};
+use super::contract_state::ContractState;
+use super::doc_comments::DocComment;
+use super::text_document::TextDocument;
+
+/// Signature of the function called by the innermost list open at the position, with
+/// the argument being written as the active parameter. Functions of other contracts
+/// called through `contract-call?` are looked up with `resolve_contract`, by name.
+pub fn signature_help(contract: &str, position: &Position, resolve_contract: &dyn Fn(&str) -> Option<ContractState>) -> Option<SignatureHelp> {
+    let offset = TextDocument::new(contract.to_string()).offset_at(*position);
+    // a list opened without a function yet is an argument of the enclosing call.
+    let lists = open_lists(&contract[..offset]);
+    let call = lists.iter().rev().find(|list| list.function().is_some())?;
+    let function = call.function()?;
+    let active_argument = call.active_argument()?;
+
+    if let Some(help) = contract_call_signature(call, resolve_contract) {
+        return Some(help);
+    }
+    if let Some(native_function) = NativeFunctions::lookup_by_name(function) {
+        return Some(api_signatures(&make_api_reference(&native_function), active_argument));
+    }
+    if let Some(define_function) = DefineFunctions::lookup_by_name(function) {
+        return Some(api_signatures(&make_define_reference(&define_function), active_argument));
+    }
+
+    let state = ContractState::new(contract);
+    let function_type = state.analysis.get_private_function(function)
+        .or_else(|| state.analysis.get_public_function_type(function))
+        .or_else(|| state.analysis.get_read_only_function_type(function))?;
+    fixed_function_signature(function, function_type, state.doc_comments.get(function), active_argument)
+}
+
+/// Signature of the function called through `contract-call?`, once the contract and
+/// the function are written.
+fn contract_call_signature(call: &OpenList, resolve_contract: &dyn Fn(&str) -> Option<ContractState>) -> Option<SignatureHelp> {
+    if call.function() != Some(NativeFunctions::ContractCall.get_name().as_str()) {
+        return None;
+    }
+    let active_argument = call.active_argument()?.checked_sub(2)?;
+    // `.counter` or `'SP000000000000000000002Q6VF78.counter`
+    let contract_name = call.elements.get(1)?.rsplit('.').next()?;
+    let function = call.elements.get(2)?;
+    let state = resolve_contract(contract_name)?;
+    let function_type = state.analysis.get_public_function_type(function)
+        .or_else(|| state.analysis.get_read_only_function_type(function))?;
+    fixed_function_signature(function, function_type, state.doc_comments.get(function.as_str()), active_argument)
+}
+
+/// Signature of a function of a contract, documented by its doc comment if any.
+fn fixed_function_signature(name: &str, function_type: &FunctionType, doc: Option<&DocComment>, active_argument: usize) -> Option<SignatureHelp> {
+    let function = match function_type {
+        FunctionType::Fixed(function) => function,
+        _ => return None
+    };
+    let FixedFunction { args, returns } = function;
+
+    let mut label = format!("({}", name);
+    let mut parameters = vec![];
+    for arg in args.iter() {
+        label.push(' ');
+        let start = label.len() as u64;
+        label.push_str(&format!("({} {})", arg.name.as_str(), arg.signature));
+        parameters.push(ParameterInformation {
+            label: ParameterLabel::LabelOffsets([start, label.len() as u64]),
+            documentation: doc
+                .and_then(|doc| doc.param(arg.name.as_str()))
+                .map(|description| Documentation::String(description.to_string())),
+        });
+    }
+    label.push_str(&format!(") -> {}", returns));
+
+    let active_parameter = if active_argument < parameters.len() {
+        Some(active_argument as i64)
+    } else {
+        None
+    };
+    Some(SignatureHelp {
+        signatures: vec![SignatureInformation {
+            label,
+            documentation: doc.map(|doc| Documentation::MarkupContent(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: doc.to_markdown(),
+            })),
+            parameters: Some(parameters),
+        }],
+        active_signature: Some(0),
+        active_parameter,
+    })
+}
+
+/// Signatures from the reference documentation. Some functions have alternative forms,
+/// e.g. `match`, which are offered as separate signatures.
+fn api_signatures(api: &FunctionAPI, active_argument: usize) -> SignatureHelp {
+    let forms = split_top_level(&api.signature, '|');
+    let input_types = split_top_level(&api.input_type, ',');
+
+    let mut active_parameter = None;
+    let signatures = forms.iter().enumerate().map(|(form_index, form)| {
+        let parameters = parameter_offsets(form);
+        // input types only line up with the parameters of single form functions.
+        let documented = forms.len() == 1 && input_types.len() == parameters.len();
+        let parameters: Vec<_> = parameters.iter().enumerate().map(|(index, (start, end))| ParameterInformation {
+            label: ParameterLabel::LabelOffsets([*start as u64, *end as u64]),
+            documentation: if documented {
+                Some(Documentation::String(input_types[index].to_string()))
+            } else {
+                None
+            },
+        }).collect();
+
+        if form_index == 0 {
+            let is_variadic = form.contains("...");
+            active_parameter = if active_argument < parameters.len() {
+                Some(active_argument as i64)
+            } else if is_variadic && !parameters.is_empty() {
+                Some(parameters.len() as i64 - 1)
+            } else {
+                None
+            };
+        }
+
+        SignatureInformation {
+            label: form.to_string(),
+            documentation: Some(Documentation::MarkupContent(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: api.description.clone(),
+            })),
+            parameters: Some(parameters),
+        }
+    }).collect();
+
+    SignatureHelp {
+        signatures,
+        active_signature: Some(0),
+        active_parameter,
+    }
+}
+
+/// Splits the text on the separator, outside of parentheses.
+fn split_top_level(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (index, character) in text.char_indices() {
+        match character {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            character if character == separator && depth == 0 => {
+                parts.push(text[start..index].trim());
+                start = index + character.len_utf8();
+            },
+            _ => {}
+        }
+    }
+    parts.push(text[start..].trim());
+    parts
+}
+
+/// Offsets of the parameters in a signature such as `(fold func list initial-value)`,
+/// skipping the function name and the `...` marking repeated parameters.
+pub fn parameter_offsets(signature: &str) -> Vec<(usize, usize)> {
+    let mut elements = vec![];
+    let mut depth = 0;
+    let mut start = None;
+    for (index, character) in signature.char_indices() {
+        match character {
+            '(' if depth == 0 => depth += 1,
+            '(' => {
+                start.get_or_insert(index);
+                depth += 1;
+            },
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    if let Some(start) = start.take() {
+                        elements.push((start, index));
+                    }
+                }
+            },
+            character if character.is_whitespace() && depth == 1 => {
+                if let Some(start) = start.take() {
+                    elements.push((start, index));
+                }
+            },
+            _ => {
+                start.get_or_insert(index);
+            }
+        }
+    }
+    elements.into_iter()
+        .skip(1)
+        .filter(|(start, end)| &signature[*start..*end] != "...")
+        .collect()
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    /// Signature labels and the label of the active parameter, at the end of the contract.
+    fn help_at_end(contract: &str, resolve_contract: &dyn Fn(&str) -> Option<ContractState>) -> Option<(Vec<String>, Option<String>)> {
+        let line = contract.matches('\n').count();
+        let character = contract.len() - contract.rfind('\n').map(|ix| ix + 1).unwrap_or(0);
+        let help = signature_help(contract, &Position::new(line as u64, character as u64), resolve_contract)?;
+        let active_label = help.active_parameter.map(|active| {
+            let signature = &help.signatures[0];
+            match signature.parameters.as_ref().unwrap()[active as usize].label {
+                ParameterLabel::LabelOffsets([start, end]) => signature.label[start as usize..end as usize].to_string(),
+                ParameterLabel::Simple(ref label) => label.clone(),
+            }
+        });
+        Some((help.signatures.into_iter().map(|signature| signature.label).collect(), active_label))
+    }
+
+    fn no_contracts(_: &str) -> Option<ContractState> {
+        None
+    }
+
+    #[test]
+    fn test_native_signatures() {
+        assert_eq!(help_at_end("(fold add-one items ", &no_contracts),
+                   Some((vec!["(fold func list initial-value)".to_string()], Some("initial-value".to_string()))));
+        assert_eq!(help_at_end("(+ u1 u2 u3 ", &no_contracts).unwrap().1, Some("i2...".to_string()));
+        assert_eq!(help_at_end("(var-set counter (", &no_contracts).unwrap().1, Some("expr1".to_string()));
+        assert_eq!(help_at_end("(let ((a u1)) ", &no_contracts).unwrap().1, Some("expr-body1".to_string()));
+        assert_eq!(help_at_end("(match value ", &no_contracts).unwrap().0.len(), 2);
+        assert_eq!(help_at_end("(not true false ", &no_contracts).unwrap().1, None);
+        assert_eq!(help_at_end("(not true)", &no_contracts), None);
+    }
+
+    #[test]
+    fn test_parameters_are_documented_with_input_types() {
+        let help = signature_help("(var-set ", &Position::new(0, 9), &no_contracts).unwrap();
+        let documentation: Vec<_> = help.signatures[0].parameters.as_ref().unwrap().iter()
+            .map(|parameter| parameter.documentation.clone())
+            .collect();
+        assert_eq!(documentation, vec![
+            Some(Documentation::String("VarName".to_string())),
+            Some(Documentation::String("AnyType".to_string())),
+        ]);
+    }
+
+    #[test]
+    fn test_user_defined_signatures() {
+        let contract = "(define-private (transfer-amount (amount uint) (recipient principal)) (ok amount))\n\
+                        (define-public (run) (transfer-amount u10 ";
+        assert_eq!(help_at_end(contract, &no_contracts), Some((
+            vec!["(transfer-amount (amount uint) (recipient principal)) -> (response uint UnknownType)".to_string()],
+            Some("(recipient principal)".to_string()))));
+
+        let counter = |name: &str| match name {
+            "counter" => Some(ContractState::new("(define-public (add (step uint) (times uint)) (ok (* step times)))")),
+            _ => None
+        };
+        let caller = "(define-public (run) (contract-call? .counter add u1 ";
+        assert_eq!(help_at_end(caller, &counter).unwrap().1, Some("(times uint)".to_string()));
+        let caller = "(define-public (run) (contract-call? .counter add";
+        assert_eq!(help_at_end(caller, &counter).unwrap().1, Some("function-name".to_string()));
+    }
+
+    #[test]
+    fn test_user_defined_signatures_are_documented() {
+        let counter = |_: &str| Some(ContractState::new(";; Adds a step, several times.\n\
+                                                        ;; @param step added each time\n\
+                                                        (define-public (add (step uint) (times uint)) (ok (* step times)))"));
+        let caller = "(define-public (run) (contract-call? .counter add u1";
+        let help = signature_help(caller, &Position::new(0, caller.len() as u64), &counter).unwrap();
+        let signature = &help.signatures[0];
+        assert_eq!(signature.documentation, Some(Documentation::MarkupContent(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: "Adds a step, several times.\n\n**Parameters**\n- `step` added each time".to_string(),
+        })));
+        let documentation: Vec<_> = signature.parameters.as_ref().unwrap().iter()
+            .map(|parameter| parameter.documentation.clone())
+            .collect();
+        assert_eq!(documentation, vec![Some(Documentation::String("added each time".to_string())), None]);
+    }
+}
@@ -0,0 +1,35 @@
+use tower_lsp::lsp_types::{Position, Range};
+
+use super::clarity::representations::Span;
+
+/// Spans are 1-based, with an inclusive end column, while LSP positions are
+/// 0-based with an exclusive end.
+pub fn span_to_range(span: &Span) -> Range {
+    Range {
+        start: Position {
+            line: span.start_line.saturating_sub(1) as u64,
+            character: span.start_column.saturating_sub(1) as u64,
+        },
+        end: Position {
+            line: span.end_line.saturating_sub(1) as u64,
+            character: span.end_column as u64,
+        },
+    }
+}
+
+/// Whether the character following the given position lies within the span.
+/// Spans left empty by synthesized expressions never contain anything.
+pub fn span_contains(span: &Span, position: &Position) -> bool {
+    if span.start_line == 0 {
+        return false;
+    }
+    let line = position.line as u32 + 1;
+    let column = position.character as u32 + 1;
+    (span.start_line, span.start_column) <= (line, column)
+        && (line, column) <= (span.end_line, span.end_column)
+}
+
+pub fn spans_overlap(a: &Span, b: &Span) -> bool {
+    (a.start_line, a.start_column) <= (b.end_line, b.end_column)
+        && (b.start_line, b.start_column) <= (a.end_line, a.end_column)
+}
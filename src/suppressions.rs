@@ -0,0 +1,99 @@
+use std::collections::{HashMap, HashSet};
+
+use super::clarity::ast::parser::{lex_with_recovery, LexItem};
+use super::clarity::diagnostic::Diagnostic;
+
+const ALLOW_DIRECTIVE: &str = "clarity-lsp: allow(";
+
+/// Diagnostic codes allowed by `;; clarity-lsp: allow(CLR-T0013, ...)` comments. A
+/// comment following code allows the codes on its line, while a comment on a line of
+/// its own allows them in the whole file.
+#[derive(Debug, Default)]
+pub struct Suppressions {
+    file: HashSet<String>,
+    lines: HashMap<u32, HashSet<String>>,
+}
+
+impl Suppressions {
+
+    pub fn new(contract: &str) -> Suppressions {
+        let mut suppressions = Suppressions::default();
+        let mut last_code_line = 0;
+        for (token, span) in lex_with_recovery(contract) {
+            let comment = match token {
+                LexItem::Whitespace => continue,
+                LexItem::Comment(comment) => comment,
+                _ => {
+                    last_code_line = span.end_line;
+                    continue;
+                }
+            };
+            let codes = match allowed_codes(&comment) {
+                Some(codes) => codes,
+                None => continue
+            };
+            if span.start_line == last_code_line {
+                suppressions.lines.entry(span.start_line).or_insert_with(HashSet::new).extend(codes);
+            } else {
+                suppressions.file.extend(codes);
+            }
+        }
+        suppressions
+    }
+
+    /// Whether the code of the diagnostic is allowed where the diagnostic starts.
+    pub fn is_suppressed(&self, diagnostic: &Diagnostic) -> bool {
+        let code = match diagnostic.code {
+            Some(ref code) => code,
+            None => return false
+        };
+        let line = diagnostic.spans.first().map_or(0, |span| span.start_line);
+        self.is_allowed(code, line)
+    }
+
+    fn is_allowed(&self, code: &str, line: u32) -> bool {
+        self.file.contains(code) || self.lines.get(&line).is_some_and(|codes| codes.contains(code))
+    }
+}
+
+fn allowed_codes(comment: &str) -> Option<Vec<String>> {
+    let directive = comment.trim_start_matches(';').trim();
+    let codes = directive.strip_prefix(ALLOW_DIRECTIVE)?;
+    let codes = &codes[..codes.find(')')?];
+    Some(codes.split(',')
+        .map(|code| code.trim().to_string())
+        .filter(|code| !code.is_empty())
+        .collect())
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::contract_state::ContractState;
+
+    #[test]
+    fn test_suppressions() {
+        let contract = ";; clarity-lsp: allow(CLR-T0059)\n\
+                        (define-public (f) (ok u1)) ;; clarity-lsp: allow(CLR-T0060, CLR-T0013)\n\
+                        (define-public (g) (ok u1)) ;; clarity-lsp: allow\n\
+                        (define-public (h) (ok u1))\n\
+                        \x20 ;;clarity-lsp: allow(CLR-P0019)";
+        let suppressions = Suppressions::new(contract);
+        assert!(suppressions.is_allowed("CLR-T0059", 4));
+        assert!(suppressions.is_allowed("CLR-P0019", 1));
+        assert!(suppressions.is_allowed("CLR-T0060", 2));
+        assert!(suppressions.is_allowed("CLR-T0013", 2));
+        assert!(!suppressions.is_allowed("CLR-T0013", 3));
+        assert!(!suppressions.is_allowed("CLR-T0001", 2));
+
+        let contract = "(define-read-only (f) (ok (+ u1 1))) ;; clarity-lsp: allow(CLR-T0013)\n\
+                        (define-read-only (g) (ok (+ u1 1)))";
+        let state = ContractState::new(contract);
+        let reported: Vec<_> = state.check_errors.iter()
+            .filter(|error| !state.suppressions.is_suppressed(&error.diagnostic))
+            .map(|error| error.diagnostic.spans[0].start_line)
+            .collect();
+        assert!(!reported.is_empty());
+        assert!(reported.iter().all(|line| *line == 2));
+    }
+}
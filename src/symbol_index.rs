@@ -0,0 +1,425 @@
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::Position;
+
+use super::clarity::ast::ContractAST;
+use super::clarity::ast::definition_sorter::DefinitionSorter;
+use super::clarity::functions::{DefineFunctions, NativeFunctions};
+use super::clarity::representations::{ClarityName, Span, SymbolicExpression, SymbolicExpressionType};
+use super::spans::span_contains;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DefinitionKind {
+    PrivateFunction,
+    PublicFunction,
+    ReadOnlyFunction,
+    Constant,
+    DataVar,
+    Map,
+    FungibleToken,
+    NonFungibleToken,
+    Trait,
+    UseTrait,
+    Parameter,
+    LetBinding,
+    MatchBinding,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Definition {
+    pub name: ClarityName,
+    pub kind: DefinitionKind,
+    /// Span of the atom introducing the name.
+    pub name_span: Span,
+    /// Span of the whole definition: the top-level expression, or the binding.
+    pub span: Span,
+    /// Span of the expression in which a local binding is visible, while top-level
+    /// definitions are visible throughout the contract.
+    pub scope: Option<Span>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reference {
+    pub span: Span,
+    /// Index of the referenced definition.
+    pub definition: usize,
+}
+
+/// Names defined by a contract, and the atoms referring to them, with local bindings
+/// resolved following the scoping rules of the type checker.
+#[derive(Debug, Default)]
+pub struct SymbolIndex {
+    pub definitions: Vec<Definition>,
+    pub references: Vec<Reference>,
+}
+
+impl SymbolIndex {
+
+    pub fn new(contract_ast: &ContractAST) -> SymbolIndex {
+        let mut index = SymbolIndex::default();
+
+        let mut top_level_definitions = HashMap::new();
+        for expr in contract_ast.expressions.iter() {
+            if let Some(definition) = top_level_definition(expr) {
+                if !top_level_definitions.contains_key(&definition.name) {
+                    top_level_definitions.insert(definition.name.clone(), index.definitions.len());
+                }
+                index.definitions.push(definition);
+            }
+        }
+
+        let mut scopes = vec![];
+        for expr in contract_ast.expressions.iter() {
+            index.visit_top_level(expr, &mut scopes);
+        }
+
+        // local bindings can not shadow top-level definitions, so the references to the latter
+        // are exactly the dependencies found by the definition sorter.
+        let top_level_references = DefinitionSorter::find_top_level_references(&contract_ast.pre_expressions)
+            .unwrap_or_default();
+        for (name, span) in top_level_references.into_iter() {
+            let already_resolved = index.references.iter().any(|reference| reference.span == span);
+            if let (Some(definition), false) = (top_level_definitions.get(&name), already_resolved) {
+                index.references.push(Reference { span, definition: *definition });
+            }
+        }
+        index.references.sort_by_key(|reference| (reference.span.start_line, reference.span.start_column));
+        index
+    }
+
+    /// The definition named at the position, either by a reference or by the definition itself.
+    pub fn definition_at(&self, position: &Position) -> Option<usize> {
+        self.occurrence_at(position).map(|(definition, _)| definition)
+    }
+
+    /// The definition named at the position, along with the span of the name.
+    pub fn occurrence_at(&self, position: &Position) -> Option<(usize, &Span)> {
+        self.references.iter()
+            .find(|reference| span_contains(&reference.span, position))
+            .map(|reference| (reference.definition, &reference.span))
+            .or_else(|| self.definitions.iter()
+                .position(|definition| span_contains(&definition.name_span, position))
+                .map(|definition| (definition, &self.definitions[definition].name_span)))
+    }
+
+    pub fn references_to(&self, definition: usize) -> impl Iterator<Item = &Reference> {
+        self.references.iter().filter(move |reference| reference.definition == definition)
+    }
+
+    fn visit_top_level(&mut self, expr: &SymbolicExpression, scopes: &mut Vec<HashMap<ClarityName, usize>>) {
+        let (define_type, args) = match DefineFunctions::try_parse(expr) {
+            Some(define) => define,
+            None => return self.visit(expr, scopes)
+        };
+        match define_type {
+            DefineFunctions::PrivateFunction | DefineFunctions::PublicFunction | DefineFunctions::ReadOnlyFunction => {
+                let signature = match args.first().and_then(|e| e.match_list()) {
+                    Some(signature) => signature,
+                    None => return
+                };
+                let mut parameters = HashMap::new();
+                for parameter in signature.iter().skip(1) {
+                    let pair = match parameter.match_list() {
+                        Some(pair) if pair.len() == 2 => pair,
+                        _ => continue
+                    };
+                    if let Some(name) = pair[0].match_atom() {
+                        parameters.insert(name.clone(), self.definitions.len());
+                        self.definitions.push(Definition {
+                            name: name.clone(),
+                            kind: DefinitionKind::Parameter,
+                            name_span: pair[0].span.clone(),
+                            span: parameter.span.clone(),
+                            scope: Some(expr.span.clone()),
+                        });
+                    }
+                    self.visit_type(&pair[1], scopes);
+                }
+                scopes.push(parameters);
+                for body in args.iter().skip(1) {
+                    self.visit(body, scopes);
+                }
+                scopes.pop();
+            },
+            DefineFunctions::Constant | DefineFunctions::FungibleToken => {
+                for value in args.iter().skip(1) {
+                    self.visit(value, scopes);
+                }
+            },
+            DefineFunctions::PersistedVariable => {
+                if let Some(initial) = args.get(2) {
+                    self.visit(initial, scopes);
+                }
+            },
+            DefineFunctions::Trait => {
+                for function in args.iter().skip(1) {
+                    self.visit_type(function, scopes);
+                }
+            },
+            DefineFunctions::Map | DefineFunctions::NonFungibleToken |
+            DefineFunctions::UseTrait | DefineFunctions::ImplTrait => {}
+        }
+    }
+
+    /// Type signatures only refer to names through trait references.
+    fn visit_type(&mut self, expr: &SymbolicExpression, scopes: &[HashMap<ClarityName, usize>]) {
+        match expr.expr {
+            SymbolicExpressionType::TraitReference(ref name, _) => self.add_reference(name, &expr.span, scopes),
+            SymbolicExpressionType::List(ref list) => {
+                for item in list.iter() {
+                    self.visit_type(item, scopes);
+                }
+            },
+            _ => {}
+        }
+    }
+
+    fn visit(&mut self, expr: &SymbolicExpression, scopes: &mut Vec<HashMap<ClarityName, usize>>) {
+        let list = match expr.expr {
+            SymbolicExpressionType::Atom(ref name) => return self.add_reference(name, &expr.span, scopes),
+            SymbolicExpressionType::TraitReference(ref name, _) => return self.add_reference(name, &expr.span, scopes),
+            SymbolicExpressionType::List(ref list) => list,
+            _ => return
+        };
+
+        let native_function = list.first()
+            .and_then(|head| head.match_atom())
+            .and_then(|name| NativeFunctions::lookup_by_name(name));
+        match native_function {
+            Some(NativeFunctions::Let) => {
+                let mut bindings = HashMap::new();
+                for binding in list.get(1).and_then(|e| e.match_list()).unwrap_or(&[]).iter() {
+                    let pair = match binding.match_list() {
+                        Some(pair) if pair.len() == 2 => pair,
+                        _ => continue
+                    };
+                    // bindings are evaluated in the enclosing scope.
+                    self.visit(&pair[1], scopes);
+                    if let Some(name) = pair[0].match_atom() {
+                        bindings.insert(name.clone(), self.definitions.len());
+                        self.definitions.push(Definition {
+                            name: name.clone(),
+                            kind: DefinitionKind::LetBinding,
+                            name_span: pair[0].span.clone(),
+                            span: binding.span.clone(),
+                            scope: Some(expr.span.clone()),
+                        });
+                    }
+                }
+                scopes.push(bindings);
+                for body in list.iter().skip(2) {
+                    self.visit(body, scopes);
+                }
+                scopes.pop();
+            },
+            Some(NativeFunctions::Match) => {
+                if let Some(input) = list.get(1) {
+                    self.visit(input, scopes);
+                }
+                match list.len() {
+                    // (match opt some-binding some-branch none-branch)
+                    5 => {
+                        self.visit_with_binding(&list[2], &list[3], scopes);
+                        self.visit(&list[4], scopes);
+                    },
+                    // (match resp ok-binding ok-branch err-binding err-branch)
+                    6 => {
+                        self.visit_with_binding(&list[2], &list[3], scopes);
+                        self.visit_with_binding(&list[4], &list[5], scopes);
+                    },
+                    _ => {
+                        for item in list.iter().skip(2) {
+                            self.visit(item, scopes);
+                        }
+                    }
+                }
+            },
+            // tuple keys are not references.
+            Some(NativeFunctions::TupleGet) => {
+                for item in list.iter().skip(2) {
+                    self.visit(item, scopes);
+                }
+            },
+            Some(NativeFunctions::TupleCons) => {
+                for entry in list.iter().skip(1) {
+                    match entry.match_list() {
+                        Some(pair) if pair.len() == 2 => self.visit(&pair[1], scopes),
+                        _ => self.visit(entry, scopes)
+                    }
+                }
+            },
+            // the function called is defined by another contract.
+            Some(NativeFunctions::ContractCall) => {
+                for (index, item) in list.iter().enumerate().skip(1) {
+                    if index != 2 {
+                        self.visit(item, scopes);
+                    }
+                }
+            },
+            // block properties are keywords.
+            Some(NativeFunctions::GetBlockInfo) => {
+                for item in list.iter().skip(2) {
+                    self.visit(item, scopes);
+                }
+            },
+            Some(_) => {
+                for item in list.iter().skip(1) {
+                    self.visit(item, scopes);
+                }
+            },
+            None => {
+                // lists headed by a list are implicit tuples, e.g. ((key value)).
+                let implicit_tuple = list.first().and_then(|head| head.match_list()).is_some();
+                for item in list.iter() {
+                    match item.match_list() {
+                        Some(pair) if implicit_tuple && pair.len() == 2 => self.visit(&pair[1], scopes),
+                        _ => self.visit(item, scopes)
+                    }
+                }
+            }
+        }
+    }
+
+    fn visit_with_binding(&mut self, binding: &SymbolicExpression, branch: &SymbolicExpression, scopes: &mut Vec<HashMap<ClarityName, usize>>) {
+        let mut bindings = HashMap::new();
+        if let Some(name) = binding.match_atom() {
+            bindings.insert(name.clone(), self.definitions.len());
+            self.definitions.push(Definition {
+                name: name.clone(),
+                kind: DefinitionKind::MatchBinding,
+                name_span: binding.span.clone(),
+                span: binding.span.clone(),
+                scope: Some(branch.span.clone()),
+            });
+        }
+        scopes.push(bindings);
+        self.visit(branch, scopes);
+        scopes.pop();
+    }
+
+    fn add_reference(&mut self, name: &ClarityName, span: &Span, scopes: &[HashMap<ClarityName, usize>]) {
+        if let Some(definition) = scopes.iter().rev().find_map(|scope| scope.get(name)) {
+            self.references.push(Reference {
+                span: span.clone(),
+                definition: *definition,
+            });
+        }
+    }
+}
+
+/// The definition introduced by a top-level expression, if any.
+fn top_level_definition(expr: &SymbolicExpression) -> Option<Definition> {
+    let (define_type, args) = DefineFunctions::try_parse(expr)?;
+    let kind = match define_type {
+        DefineFunctions::PrivateFunction => DefinitionKind::PrivateFunction,
+        DefineFunctions::PublicFunction => DefinitionKind::PublicFunction,
+        DefineFunctions::ReadOnlyFunction => DefinitionKind::ReadOnlyFunction,
+        DefineFunctions::Constant => DefinitionKind::Constant,
+        DefineFunctions::PersistedVariable => DefinitionKind::DataVar,
+        DefineFunctions::Map => DefinitionKind::Map,
+        DefineFunctions::FungibleToken => DefinitionKind::FungibleToken,
+        DefineFunctions::NonFungibleToken => DefinitionKind::NonFungibleToken,
+        DefineFunctions::Trait => DefinitionKind::Trait,
+        DefineFunctions::UseTrait => DefinitionKind::UseTrait,
+        DefineFunctions::ImplTrait => return None,
+    };
+    let name_expr = match kind {
+        DefinitionKind::PrivateFunction | DefinitionKind::PublicFunction | DefinitionKind::ReadOnlyFunction =>
+            args.first()?.match_list()?.first()?,
+        _ => args.first()?
+    };
+    Some(Definition {
+        name: name_expr.match_atom()?.clone(),
+        kind,
+        name_span: name_expr.span.clone(),
+        span: expr.span.clone(),
+        scope: None,
+    })
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::contract_state::ContractState;
+
+    /// Position of the n-th occurrence of a word in the contract.
+    fn position_of(contract: &str, word: &str, occurrence: usize) -> Position {
+        let offset = contract.match_indices(word).nth(occurrence).unwrap().0;
+        let line = contract[..offset].matches('\n').count();
+        let line_start = contract[..offset].rfind('\n').map(|ix| ix + 1).unwrap_or(0);
+        Position::new(line as u64, (offset - line_start) as u64)
+    }
+
+    fn resolve(contract: &str, word: &str, occurrence: usize) -> Option<(DefinitionKind, Span)> {
+        let state = ContractState::new(contract);
+        let index = SymbolIndex::new(&state.contract_ast);
+        index.definition_at(&position_of(contract, word, occurrence))
+            .map(|definition| (index.definitions[definition].kind, index.definitions[definition].span.clone()))
+    }
+
+    fn span(start_line: u32, start_column: u32, end_line: u32, end_column: u32) -> Span {
+        Span { start_line, start_column, end_line, end_column }
+    }
+
+    #[test]
+    fn test_top_level_definitions_are_resolved() {
+        let contract = "(define-constant owner tx-sender)\n\
+                        (define-data-var total uint u0)\n\
+                        (define-map balances ((who principal)) ((amount uint)))\n\
+                        (define-fungible-token coin)\n\
+                        (define-private (is-owner) (is-eq tx-sender owner))\n\
+                        (define-public (mint (amount uint))\n\
+                        \x20 (begin\n\
+                        \x20   (asserts! (is-owner) (err u1))\n\
+                        \x20   (var-set total (+ (var-get total) amount))\n\
+                        \x20   (map-set balances ((who tx-sender)) ((amount amount)))\n\
+                        \x20   (ft-mint? coin amount tx-sender)))";
+        assert_eq!(resolve(contract, "owner", 2), Some((DefinitionKind::Constant, span(1, 1, 1, 33))));
+        assert_eq!(resolve(contract, "is-owner", 1), Some((DefinitionKind::PrivateFunction, span(5, 1, 5, 51))));
+        assert_eq!(resolve(contract, "total", 2), Some((DefinitionKind::DataVar, span(2, 1, 2, 31))));
+        assert_eq!(resolve(contract, "balances", 1).map(|r| r.0), Some(DefinitionKind::Map));
+        assert_eq!(resolve(contract, "coin", 1).map(|r| r.0), Some(DefinitionKind::FungibleToken));
+        // map keys are not references, map values are.
+        assert_eq!(resolve(contract, "amount", 3), None);
+        assert_eq!(resolve(contract, "amount", 4), Some((DefinitionKind::Parameter, span(6, 22, 6, 34))));
+    }
+
+    #[test]
+    fn test_local_bindings_are_resolved() {
+        let contract = "(define-read-only (f (x int))\n\
+                        \x20 (let ((q (+ x 1)) (x 2))\n\
+                        \x20   (match (some q) w (+ x w) q)))";
+        // let bindings are evaluated in the enclosing scope.
+        assert_eq!(resolve(contract, "x", 1), Some((DefinitionKind::Parameter, span(1, 22, 1, 28))));
+        assert_eq!(resolve(contract, "x", 3), Some((DefinitionKind::LetBinding, span(2, 21, 2, 25))));
+        assert_eq!(resolve(contract, "q", 1), Some((DefinitionKind::LetBinding, span(2, 9, 2, 19))));
+        assert_eq!(resolve(contract, "w", 1), Some((DefinitionKind::MatchBinding, span(3, 21, 3, 21))));
+        assert_eq!(resolve(contract, "q", 2), Some((DefinitionKind::LetBinding, span(2, 9, 2, 19))));
+    }
+
+    #[test]
+    fn test_references_follow_scoping() {
+        let contract = "(define-non-fungible-token badge uint)\n\
+                        (define-map owners ((id uint)) ((owner principal)))\n\
+                        (define-data-var last-id uint u0)\n\
+                        (define-public (claim)\n\
+                        \x20 (let ((id (+ (var-get last-id) u1)))\n\
+                        \x20   (var-set last-id id)\n\
+                        \x20   (map-set owners {id: id} {owner: tx-sender})\n\
+                        \x20   (nft-mint? badge id tx-sender)))\n\
+                        (define-read-only (owner-of (id uint))\n\
+                        \x20 (get owner (map-get? owners {id: id})))";
+        let state = ContractState::new(contract);
+        let index = SymbolIndex::new(&state.contract_ast);
+        let occurrences = |word: &str, occurrence: usize| -> Vec<u32> {
+            let definition = index.definition_at(&position_of(contract, word, occurrence)).unwrap();
+            index.references_to(definition).map(|reference| reference.span.start_line).collect()
+        };
+        assert_eq!(occurrences("badge", 0), vec![8]);
+        assert_eq!(occurrences("owners", 0), vec![7, 10]);
+        assert_eq!(occurrences("last-id", 0), vec![5, 6]);
+        // the let binding and the parameter are distinct, and tuple keys are not references.
+        assert_eq!(occurrences("id", 2), vec![6, 7, 8]);
+        assert_eq!(occurrences("id", 9), vec![10]);
+    }
+}
@@ -0,0 +1,184 @@
+use tower_lsp::lsp_types::{Position, TextDocumentContentChangeEvent};
+
+/// In-memory copy of a buffer opened in the editor, kept in sync through
+/// `textDocument/didChange` notifications.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextDocument {
+    text: String,
+}
+
+impl TextDocument {
+
+    pub fn new(text: String) -> TextDocument {
+        TextDocument { text }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Applies a content change: ranged changes are spliced in place, while a change
+    /// without range replaces the whole document.
+    pub fn apply_change(&mut self, change: &TextDocumentContentChangeEvent) {
+        match change.range {
+            Some(range) => {
+                let start = self.offset_at(range.start);
+                let end = self.offset_at(range.end).max(start);
+                self.text.replace_range(start..end, &change.text);
+            },
+            None => {
+                self.text = change.text.clone();
+            }
+        }
+    }
+
+    /// Converts an LSP position, expressed in UTF-16 code units, into a byte offset.
+    /// Positions past the end of a line are clamped to the end of that line, and
+    /// positions past the last line are clamped to the end of the document.
+    pub fn offset_at(&self, position: Position) -> usize {
+        let mut line_start = 0;
+        for _ in 0..position.line {
+            match self.text[line_start..].find('\n') {
+                Some(index) => line_start += index + 1,
+                None => return self.text.len(),
+            }
+        }
+
+        let mut offset = line_start;
+        let mut character = 0;
+        let mut chars = self.text[line_start..].chars().peekable();
+        while let Some(c) = chars.next() {
+            if character >= position.character || c == '\n' {
+                break;
+            }
+            if c == '\r' && chars.peek() == Some(&'\n') {
+                break;
+            }
+            character += c.len_utf16() as u64;
+            offset += c.len_utf8();
+        }
+        offset
+    }
+
+    /// Converts a byte offset into an LSP position, expressed in UTF-16 code units.
+    pub fn position_at(&self, offset: usize) -> Position {
+        let mut position = Position::new(0, 0);
+        for (index, c) in self.text.char_indices() {
+            if index >= offset {
+                break;
+            }
+            if c == '\n' {
+                position.line += 1;
+                position.character = 0;
+            } else {
+                position.character += c.len_utf16() as u64;
+            }
+        }
+        position
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use tower_lsp::lsp_types::Range;
+
+    /// Reference model of the buffer held by the editor: UTF-16 code units, split on '\n'.
+    struct EditorBuffer {
+        lines: Vec<Vec<u16>>,
+    }
+
+    impl EditorBuffer {
+        fn new(text: &str) -> EditorBuffer {
+            EditorBuffer {
+                lines: text.split('\n').map(|l| l.encode_utf16().collect()).collect()
+            }
+        }
+
+        fn text(&self) -> String {
+            let lines: Vec<String> = self.lines.iter()
+                .map(|l| String::from_utf16(l).unwrap())
+                .collect();
+            lines.join("\n")
+        }
+
+        fn edit(&mut self, range: Range, new_text: &str) {
+            let start = range.start;
+            let end = range.end;
+            let head = self.lines[start.line as usize][..start.character as usize].to_vec();
+            let tail = self.lines[end.line as usize][end.character as usize..].to_vec();
+            let mut replacement = head;
+            replacement.extend(new_text.encode_utf16());
+            replacement.extend(tail);
+            let new_lines = EditorBuffer::new(&String::from_utf16(&replacement).unwrap()).lines;
+            self.lines.splice(start.line as usize..=end.line as usize, new_lines);
+        }
+
+        /// Picks a position that an editor could emit, i.e. never inside a surrogate pair.
+        fn position(&self, seed: u64) -> Position {
+            let line = (seed % self.lines.len() as u64) as usize;
+            let units = &self.lines[line];
+            let mut character = ((seed / 7) % (units.len() as u64 + 1)) as usize;
+            if character > 0 && character < units.len() && (0xDC00..0xE000).contains(&units[character]) {
+                character -= 1;
+            }
+            Position::new(line as u64, character as u64)
+        }
+    }
+
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next(&mut self) -> u64 {
+            self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            self.0 >> 33
+        }
+    }
+
+    fn change(range: Option<Range>, text: &str) -> TextDocumentContentChangeEvent {
+        TextDocumentContentChangeEvent { range, range_length: None, text: text.to_string() }
+    }
+
+    #[test]
+    fn test_position_mapping_counts_utf16_units() {
+        let document = TextDocument::new("(ok \"é\")\n;; 🦀 u1\n(ok u2)".to_string());
+        let crab = document.text().find('🦀').unwrap();
+        assert_eq!(document.position_at(crab), Position::new(1, 3));
+        assert_eq!(document.offset_at(Position::new(1, 3)), crab);
+        assert_eq!(document.offset_at(Position::new(1, 6)), crab + '🦀'.len_utf8() + 1);
+        assert_eq!(document.offset_at(Position::new(1, 100)), document.text().rfind('\n').unwrap());
+        assert_eq!(document.offset_at(Position::new(9, 0)), document.text().len());
+    }
+
+    #[test]
+    fn test_ranged_changes() {
+        let mut document = TextDocument::new("(define-public (foo)\n  (ok u1))".to_string());
+        document.apply_change(&change(Some(Range::new(Position::new(0, 16), Position::new(0, 19))), "bar"));
+        document.apply_change(&change(Some(Range::new(Position::new(1, 6), Position::new(1, 8))), "u42"));
+        assert_eq!(document.text(), "(define-public (bar)\n  (ok u42))");
+        document.apply_change(&change(Some(Range::new(Position::new(0, 20), Position::new(1, 2))), " "));
+        assert_eq!(document.text(), "(define-public (bar) (ok u42))");
+        document.apply_change(&change(None, "(ok true)"));
+        assert_eq!(document.text(), "(ok true)");
+    }
+
+    #[test]
+    fn test_arbitrary_edit_sequences_match_editor() {
+        let fragments = ["", "a", "(", ")", "\n", "u1 ", "é", "🦀", "\n;; ü\n", "(define-read-only (f) u1)\n"];
+        let mut rng = Lcg(42);
+        for _ in 0..50 {
+            let initial = "(define-data-var counter uint u0)\n;; naïve 🦀\n(var-get counter)";
+            let mut editor = EditorBuffer::new(initial);
+            let mut document = TextDocument::new(initial.to_string());
+            for _ in 0..40 {
+                let a = editor.position(rng.next());
+                let b = editor.position(rng.next());
+                let (start, end) = if (a.line, a.character) <= (b.line, b.character) { (a, b) } else { (b, a) };
+                let text = fragments[(rng.next() % fragments.len() as u64) as usize];
+                editor.edit(Range::new(start, end), text);
+                document.apply_change(&change(Some(Range::new(start, end)), text));
+                assert_eq!(document.text(), editor.text());
+            }
+        }
+    }
+}
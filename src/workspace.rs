@@ -0,0 +1,46 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use tower_lsp::lsp_types::Url;
+
+const CONTRACT_EXTENSION: &str = "clar";
+
+/// Lists the contracts found under the workspace folders, skipping hidden directories.
+pub fn find_contracts(roots: &[PathBuf]) -> Vec<Url> {
+    let mut contracts = vec![];
+    for root in roots.iter() {
+        collect_contracts(root, &mut contracts);
+    }
+    contracts
+}
+
+fn collect_contracts(directory: &Path, contracts: &mut Vec<Url>) {
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(_) => return
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        let is_hidden = path.file_name()
+            .and_then(|name| name.to_str())
+            .map(|name| name.starts_with('.'))
+            .unwrap_or(false);
+        if is_hidden {
+            continue;
+        }
+        if path.is_dir() {
+            collect_contracts(&path, contracts);
+        } else if path.extension().and_then(|extension| extension.to_str()) == Some(CONTRACT_EXTENSION) {
+            if let Ok(uri) = Url::from_file_path(&path) {
+                contracts.push(uri);
+            }
+        }
+    }
+}
+
+/// Contracts are deployed under the name of their file, e.g. `.counter` for `counter.clar`.
+pub fn contract_name(uri: &Url) -> Option<String> {
+    let path = uri.to_file_path().ok()?;
+    let name = path.file_stem()?.to_str()?;
+    Some(name.to_string())
+}
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::{DocumentSymbol, Location, SymbolInformation, Url};
+
+use super::contract_state::ContractState;
+use super::document_symbols::document_symbols;
+
+/// Symbols defined by the contracts of the workspace, kept per contract so that a
+/// contract can be re-indexed or dropped on its own when its file changes.
+#[derive(Debug, Default)]
+pub struct WorkspaceSymbols {
+    contracts: HashMap<Url, Vec<SymbolInformation>>,
+}
+
+impl WorkspaceSymbols {
+
+    pub fn update(&mut self, uri: Url, state: &ContractState) {
+        let mut symbols = vec![];
+        flatten_symbols(&uri, document_symbols(state), None, &mut symbols);
+        self.contracts.insert(uri, symbols);
+    }
+
+    pub fn remove(&mut self, uri: &Url) {
+        self.contracts.remove(uri);
+    }
+
+    /// Symbols fuzzy matching the query, best matches first.
+    pub fn search(&self, query: &str) -> Vec<SymbolInformation> {
+        let mut matches: Vec<_> = self.contracts.values()
+            .flatten()
+            .filter_map(|symbol| fuzzy_match(query, &symbol.name).map(|score| (score, symbol)))
+            .collect();
+        matches.sort_by(|(score, symbol), (other_score, other_symbol)| {
+            score.cmp(other_score).then_with(|| symbol.name.cmp(&other_symbol.name))
+        });
+        matches.into_iter().map(|(_, symbol)| symbol.clone()).collect()
+    }
+}
+
+fn flatten_symbols(uri: &Url, symbols: Vec<DocumentSymbol>, container_name: Option<&str>, flattened: &mut Vec<SymbolInformation>) {
+    for symbol in symbols.into_iter() {
+        flattened.push(SymbolInformation {
+            name: symbol.name.clone(),
+            kind: symbol.kind,
+            deprecated: None,
+            location: Location::new(uri.clone(), symbol.range),
+            container_name: container_name.map(|name| name.to_string()),
+        });
+        if let Some(children) = symbol.children {
+            flatten_symbols(uri, children, Some(&symbol.name), flattened);
+        }
+    }
+}
+
+/// Matches names containing the characters of the query in order, ignoring case.
+/// Lower scores are better: names starting with the query rank first, then names
+/// containing it, then scattered matches by how spread out they are.
+pub fn fuzzy_match(query: &str, name: &str) -> Option<usize> {
+    let query = query.to_lowercase();
+    let name = name.to_lowercase();
+    if name.starts_with(&query) {
+        return Some(0);
+    }
+    if name.contains(&query) {
+        return Some(1);
+    }
+
+    let mut name_chars = name.chars().enumerate();
+    let mut first_match = None;
+    let mut last_match = 0;
+    for query_char in query.chars() {
+        let (position, _) = name_chars.find(|(_, name_char)| *name_char == query_char)?;
+        first_match.get_or_insert(position);
+        last_match = position;
+    }
+    Some(2 + last_match - first_match.unwrap_or(0))
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_match() {
+        assert_eq!(fuzzy_match("get", "get-balance"), Some(0));
+        assert_eq!(fuzzy_match("BAL", "get-balance"), Some(1));
+        assert_eq!(fuzzy_match("gb", "get-balance"), Some(6));
+        assert_eq!(fuzzy_match("", "get-balance"), Some(0));
+        assert_eq!(fuzzy_match("bg", "get-balance"), None);
+    }
+
+    #[test]
+    fn test_workspace_symbols_search() {
+        let token = Url::parse("file:///workspace/token.clar").unwrap();
+        let market = Url::parse("file:///workspace/market.clar").unwrap();
+        let mut symbols = WorkspaceSymbols::default();
+        symbols.update(token.clone(), &ContractState::new("(define-fungible-token gold)\n(define-map balances principal uint)"));
+        symbols.update(market.clone(), &ContractState::new("(define-trait token ((transfer (uint) (response bool uint))))"));
+
+        let names: Vec<_> = symbols.search("t").into_iter()
+            .map(|symbol| (symbol.name, symbol.location.uri, symbol.container_name))
+            .collect();
+        assert_eq!(names, vec![
+            ("token".to_string(), market.clone(), None),
+            ("transfer".to_string(), market.clone(), Some("token".to_string())),
+        ]);
+
+        symbols.remove(&market);
+        assert_eq!(symbols.search("gd").len(), 1);
+        assert!(symbols.search("t").is_empty());
+    }
+}